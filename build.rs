@@ -0,0 +1,18 @@
+fn main() {
+    let ac = autocfg::new();
+    // `ErrorKind`s stabilized after our oldest supported compiler.
+    ac.emit_path_cfg("std::io::ErrorKind::IsADirectory", "io_error_more");
+    ac.emit_path_cfg(
+        "std::io::ErrorKind::InvalidFilename",
+        "io_error_invalid_filename",
+    );
+    ac.emit_path_cfg(
+        "std::io::ErrorKind::QuotaExceeded",
+        "io_error_quota_exceeded",
+    );
+    ac.emit_path_cfg(
+        "std::io::ErrorKind::CrossesDevices",
+        "io_error_crosses_devices",
+    );
+    autocfg::rerun_path("build.rs");
+}
@@ -0,0 +1,640 @@
+use std::io::{self, SeekFrom};
+
+use crate::{context, context::context_fields};
+
+/// Wraps a stream, tagging every error it returns with a human name for it,
+/// so code juggling several streams can tell from logs which one failed.
+///
+/// ```
+/// use std::io::{self, Write as _};
+/// use io_extra::{test::ClosedWriter, Named};
+///
+/// let mut upstream = Named::new("client→upstream leg", ClosedWriter::default());
+/// let e = upstream.write_all(b"GET /").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+/// assert_eq!(format!("{:#}", e), "client→upstream leg: writer is closed");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Named<T> {
+    name: String,
+    inner: T,
+}
+
+impl<T> Named<T> {
+    /// Wrap `inner`, calling it `name`.
+    pub fn new(name: impl Into<String>, inner: T) -> Self {
+        Self {
+            name: name.into(),
+            inner,
+        }
+    }
+    /// The name of the inner stream.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get a reference to the inner stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+    /// Unwrap the inner stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: io::Read> io::Read for Named<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(|e| context(e, &self.name))
+    }
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner
+            .read_vectored(bufs)
+            .map_err(|e| context(e, &self.name))
+    }
+}
+
+impl<T: io::BufRead> io::BufRead for Named<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf().map_err(|e| context(e, &self.name))
+    }
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+impl<T: io::Write> io::Write for Named<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf).map_err(|e| context(e, &self.name))
+    }
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.inner
+            .write_vectored(bufs)
+            .map_err(|e| context(e, &self.name))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().map_err(|e| context(e, &self.name))
+    }
+}
+
+impl<T: io::Seek> io::Seek for Named<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos).map_err(|e| context(e, &self.name))
+    }
+}
+
+/// Wraps a reader, counting the bytes consumed from it, and tagging every error
+/// it returns with "at byte offset `n`", and an `offset`
+/// [field](crate::fields()).
+///
+/// Parsers may tag their own errors, like bad magic numbers, with
+/// [`at_offset`](Self::at_offset).
+///
+/// ```
+/// use std::io::{self, Read as _};
+/// use io_extra::{fields, TrackedReader};
+///
+/// let mut r = TrackedReader::new(&b"PK\x03\x04\x14\x00"[..]);
+/// let mut magic = [0; 4];
+/// r.read_exact(&mut magic)?;
+/// assert_eq!(r.offset(), 4);
+///
+/// let e = r.read_exact(&mut [0; 4]).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(format!("{:#}", e), "at byte offset 6: failed to fill whole buffer");
+/// assert!(fields(&e).eq([("offset", "6")]));
+///
+/// let e = r.at_offset(io::Error::new(io::ErrorKind::InvalidData, "bad version"));
+/// assert_eq!(format!("{:#}", e), "at byte offset 6: bad version");
+/// # Ok::<_, io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrackedReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R> TrackedReader<R> {
+    /// Wrap `inner`, counting from offset zero.
+    pub fn new(inner: R) -> Self {
+        Self { inner, offset: 0 }
+    }
+    /// The number of bytes consumed, or the position after a seek.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// Tag `e` with the current offset.
+    pub fn at_offset(&self, e: io::Error) -> io::Error {
+        at_offset(e, self.offset)
+    }
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner reader.
+    ///
+    /// Reading from it directly will put the offset out of step.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwrap the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+fn at_offset(e: io::Error, offset: u64) -> io::Error {
+    context_fields(
+        e,
+        format_args!("at byte offset {}", offset),
+        [("offset", offset)],
+    )
+}
+
+impl<R: io::Read> io::Read for TrackedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Ok(n) => {
+                self.offset += n as u64;
+                Ok(n)
+            }
+            Err(e) => Err(self.at_offset(e)),
+        }
+    }
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        match self.inner.read_vectored(bufs) {
+            Ok(n) => {
+                self.offset += n as u64;
+                Ok(n)
+            }
+            Err(e) => Err(self.at_offset(e)),
+        }
+    }
+    /// Overridden so that running out of input is tagged too.
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => {
+                    return Err(self.at_offset(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    )))
+                }
+                Ok(n) => buf = &mut buf[n..],
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for TrackedReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let offset = self.offset;
+        self.inner.fill_buf().map_err(|e| at_offset(e, offset))
+    }
+    fn consume(&mut self, amt: usize) {
+        self.offset += amt as u64;
+        self.inner.consume(amt)
+    }
+}
+
+impl<R: io::Seek> io::Seek for TrackedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self.inner.seek(pos) {
+            Ok(offset) => {
+                self.offset = offset;
+                Ok(offset)
+            }
+            Err(e) => Err(self.at_offset(e)),
+        }
+    }
+}
+
+/// Wraps a reader, attaching a fixed context message, like the name of the
+/// file or stream, to every error it returns.
+///
+/// Made with [`context_err`](ReadExt::context_err).
+/// Unlike [`Named`], this is only a reader, even if the inner stream is also a
+/// writer.
+#[derive(Debug, Clone, Default)]
+pub struct ContextReader<R>(Named<R>);
+
+impl<R> ContextReader<R> {
+    /// Wrap `inner`, attaching `context` to its errors.
+    pub fn new(context: impl Into<String>, inner: R) -> Self {
+        Self(Named::new(context, inner))
+    }
+    /// The context attached to errors.
+    pub fn context(&self) -> &str {
+        self.0.name()
+    }
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        self.0.get_ref()
+    }
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.0.get_mut()
+    }
+    /// Unwrap the inner reader.
+    pub fn into_inner(self) -> R {
+        self.0.into_inner()
+    }
+}
+
+impl<R: io::Read> io::Read for ContextReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for ContextReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+/// Wraps a writer, attaching a fixed context message, like the name of the
+/// file or stream, to every error it returns.
+///
+/// Made with [`context_err`](WriteExt::context_err).
+/// Unlike [`Named`], this is only a writer, even if the inner stream is also a
+/// reader.
+#[derive(Debug, Clone, Default)]
+pub struct ContextWriter<W>(Named<W>);
+
+impl<W> ContextWriter<W> {
+    /// Wrap `inner`, attaching `context` to its errors.
+    pub fn new(context: impl Into<String>, inner: W) -> Self {
+        Self(Named::new(context, inner))
+    }
+    /// The context attached to errors.
+    pub fn context(&self) -> &str {
+        self.0.name()
+    }
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
+    /// Get a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.0.get_mut()
+    }
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.0.into_inner()
+    }
+}
+
+impl<W: io::Write> io::Write for ContextWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// An extension trait for [`Read`](io::Read)ers.
+///
+/// ```
+/// use std::io::{self, Read as _};
+/// use io_extra::{test::ClosedReader, ReadExt as _};
+///
+/// let mut input = ClosedReader::new(io::ErrorKind::ConnectionReset).context_err("reading stdin");
+/// let e = input.read(&mut [0; 64]).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+/// assert_eq!(format!("{:#}", e), "reading stdin: reader is closed");
+/// ```
+///
+/// For streams which are also writers, like [`File`](std::fs::File), this
+/// method is ambiguous with [`WriteExt::context_err`] if both traits are in
+/// scope: call it as `ReadExt::context_err(file, ..)`, or use [`Named`].
+pub trait ReadExt: io::Read + Sized {
+    /// Attach `context` to every error from this reader.
+    fn context_err(self, context: impl Into<String>) -> ContextReader<Self> {
+        ContextReader::new(context, self)
+    }
+}
+
+impl<R: io::Read> ReadExt for R {}
+
+/// An extension trait for [`Write`](io::Write)rs.
+///
+/// ```
+/// use std::io::{self, Write as _};
+/// use io_extra::{test::ClosedWriter, WriteExt as _};
+///
+/// let mut output = ClosedWriter::default().context_err("writing report.csv");
+/// let e = output.write_all(b"id,name").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+/// assert_eq!(format!("{:#}", e), "writing report.csv: writer is closed");
+/// ```
+///
+/// See [`ReadExt`] for streams which are also readers.
+pub trait WriteExt: io::Write + Sized {
+    /// Attach `context` to every error from this writer.
+    fn context_err(self, context: impl Into<String>) -> ContextWriter<Self> {
+        ContextWriter::new(context, self)
+    }
+}
+
+impl<W: io::Write> WriteExt for W {}
+
+/// Wraps a reader whose stream may end with an optional trailing section, like
+/// an archive footer.
+///
+/// See [`read_optional_exact`](Self::read_optional_exact).
+///
+/// ```
+/// use std::io;
+/// use io_extra::OptionalTail;
+///
+/// let mut footer = [0; 4];
+///
+/// // No trailer at all.
+/// assert!(!OptionalTail::new(&b""[..]).read_optional_exact(&mut footer)?);
+///
+/// // A complete trailer.
+/// assert!(OptionalTail::new(&b"\xca\xfe\xba\xbe"[..]).read_optional_exact(&mut footer)?);
+///
+/// // A truncated trailer.
+/// let e = OptionalTail::new(&b"\xca\xfe"[..])
+///     .read_optional_exact(&mut footer)
+///     .unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(e.to_string(), "trailer is truncated: got 2 of 4 bytes");
+/// # Ok::<_, io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OptionalTail<R> {
+    inner: R,
+}
+
+impl<R> OptionalTail<R> {
+    /// Wrap `inner`.
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwrap the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: io::Read> OptionalTail<R> {
+    /// Fill `buf` exactly, returning `false` if the stream ended before any
+    /// bytes were read (i.e the trailer is absent).
+    ///
+    /// If the stream ends part way through `buf`, an
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error with the counts is
+    /// returned.
+    pub fn read_optional_exact(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.inner.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!(
+                            "trailer is truncated: got {} of {} bytes",
+                            filled,
+                            buf.len()
+                        ),
+                    ))
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: io::Read> io::Read for OptionalTail<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+/// A line ending, for [`NormalizeNewlines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Newline {
+    /// `\n`, as on Unix.
+    Lf,
+    /// `\r\n`, as on Windows and in many network protocols.
+    CrLf,
+}
+
+impl Newline {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Newline::Lf => b"\n",
+            Newline::CrLf => b"\r\n",
+        }
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Newline::Lf => "LF",
+            Newline::CrLf => "CRLF",
+        }
+    }
+}
+
+/// Wraps a reader, converting every line ending to the same [`Newline`].
+///
+/// By default, `\n`, `\r\n` and bare `\r` are all accepted.
+/// In [strict](Self::strict) mode, a bare `\r`, or a line ending which differs
+/// from the first in the stream, is an [`InvalidData`](io::ErrorKind::InvalidData) error
+/// naming the (1-based) line it ends.
+/// Any text before the offending line ending is returned first.
+///
+/// ```
+/// use std::io::{self, Read as _};
+/// use io_extra::{Newline, NormalizeNewlines};
+///
+/// let mut s = String::new();
+/// NormalizeNewlines::new(&b"a\r\nb\nc\rd"[..], Newline::Lf).read_to_string(&mut s)?;
+/// assert_eq!(s, "a\nb\nc\nd");
+///
+/// let mut s = String::new();
+/// let e = NormalizeNewlines::new(&b"a\r\nb\r\nc\nd"[..], Newline::CrLf)
+///     .strict(true)
+///     .read_to_string(&mut s)
+///     .unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(e.to_string(), "line 3 ends with LF, but line 1 ends with CRLF");
+/// assert_eq!(s, "a\r\nb\r\nc");
+/// # Ok::<_, io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct NormalizeNewlines<R> {
+    inner: R,
+    newline: Newline,
+    strict: bool,
+    /// The line being read, from 1.
+    line: u64,
+    /// The first line ending in the stream, and the line it ends.
+    first: Option<(u64, Newline)>,
+    /// Whether the last byte read was a `\r`.
+    cr: bool,
+    input: Box<[u8]>,
+    /// The unprocessed part of `input`.
+    start: usize,
+    end: usize,
+    eof: bool,
+    output: Vec<u8>,
+    /// How much of `output` has been returned.
+    returned: usize,
+    /// An error to return once `output` is drained.
+    error: Option<io::Error>,
+}
+
+impl<R> NormalizeNewlines<R> {
+    /// Wrap `inner`, converting line endings to `newline`.
+    pub fn new(inner: R, newline: Newline) -> Self {
+        Self {
+            inner,
+            newline,
+            strict: false,
+            line: 1,
+            first: None,
+            cr: false,
+            input: vec![0; 8 * 1024].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            eof: false,
+            output: Vec::new(),
+            returned: 0,
+            error: None,
+        }
+    }
+    /// Fail on bare `\r`s and mixed line endings.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwrap the inner reader, discarding any buffered input.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+    /// Record a line ending, which is [`None`] for a bare `\r`.
+    fn newline(&mut self, found: Option<Newline>) -> io::Result<()> {
+        let line = self.line;
+        self.line += 1;
+        if self.strict {
+            match (found, self.first) {
+                (None, _) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {} ends with a bare CR", line),
+                    ))
+                }
+                (Some(found), Some((first_line, first))) if found != first => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "line {} ends with {}, but line {} ends with {}",
+                            line,
+                            found.name(),
+                            first_line,
+                            first.name()
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if let (Some(found), None) = (found, self.first) {
+            self.first = Some((line, found))
+        }
+        self.output.extend_from_slice(self.newline.as_bytes());
+        Ok(())
+    }
+    /// Convert the buffered input into `output`, stopping at the first error.
+    fn process(&mut self) -> io::Result<()> {
+        while self.start < self.end {
+            let byte = self.input[self.start];
+            self.start += 1;
+            match (std::mem::take(&mut self.cr), byte) {
+                (true, b'\n') => self.newline(Some(Newline::CrLf))?,
+                (true, _) => {
+                    // Don't consume the byte after a bare `\r` if it fails.
+                    self.start -= 1;
+                    self.newline(None)?;
+                }
+                (false, b'\r') => self.cr = true,
+                (false, b'\n') => self.newline(Some(Newline::Lf))?,
+                (false, _) => self.output.push(byte),
+            }
+        }
+        if self.eof && std::mem::take(&mut self.cr) {
+            self.newline(None)?
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for NormalizeNewlines<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.returned < self.output.len() {
+                let n = (self.output.len() - self.returned).min(buf.len());
+                buf[..n].copy_from_slice(&self.output[self.returned..self.returned + n]);
+                self.returned += n;
+                return Ok(n);
+            }
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            if self.eof {
+                return Ok(0);
+            }
+            self.output.clear();
+            self.returned = 0;
+            if self.start == self.end {
+                match self.inner.read(&mut self.input) {
+                    Ok(n) => {
+                        (self.start, self.end) = (0, n);
+                        self.eof = n == 0;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            self.error = self.process().err();
+        }
+    }
+}
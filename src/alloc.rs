@@ -0,0 +1,57 @@
+//! Fallible allocation, surfaced as [`io::Error`]s of kind [`OutOfMemory`], so
+//! parsers can honor allocation limits while staying in [`io::Result`].
+//!
+//! ```
+//! use std::io;
+//! use io_extra::alloc::{string_with_capacity_io, vec_with_capacity_io};
+//!
+//! let v = vec_with_capacity_io::<u32>(16).unwrap();
+//! assert!(v.capacity() >= 16);
+//!
+//! let e = string_with_capacity_io(usize::MAX).unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::OutOfMemory);
+//! ```
+
+use std::{
+    collections::TryReserveError,
+    io::{self, ErrorKind::OutOfMemory},
+    mem,
+};
+
+fn out_of_memory<T>(additional: usize, e: TryReserveError) -> io::Error {
+    io::Error::new(
+        OutOfMemory,
+        format!(
+            "couldn't allocate {} more elements of {} bytes: {}",
+            additional,
+            mem::size_of::<T>(),
+            e
+        ),
+    )
+}
+
+/// Create a [`Vec`] with capacity for at least `n` elements.
+pub fn vec_with_capacity_io<T>(n: usize) -> io::Result<Vec<T>> {
+    let mut v = Vec::new();
+    reserve_io(&mut v, n)?;
+    Ok(v)
+}
+
+/// Create a [`String`] with capacity for at least `n` bytes.
+pub fn string_with_capacity_io(n: usize) -> io::Result<String> {
+    let mut s = String::new();
+    s.try_reserve(n).map_err(|e| out_of_memory::<u8>(n, e))?;
+    Ok(s)
+}
+
+/// Reserve capacity for at least `additional` more elements in `v`.
+pub fn reserve_io<T>(v: &mut Vec<T>, additional: usize) -> io::Result<()> {
+    v.try_reserve(additional)
+        .map_err(|e| out_of_memory::<T>(additional, e))
+}
+
+/// Reserve capacity for exactly `additional` more elements in `v`.
+pub fn reserve_exact_io<T>(v: &mut Vec<T>, additional: usize) -> io::Result<()> {
+    v.try_reserve_exact(additional)
+        .map_err(|e| out_of_memory::<T>(additional, e))
+}
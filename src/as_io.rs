@@ -0,0 +1,93 @@
+use std::{error::Error, io};
+
+/// Access the [`io::Error`] inside various error types, so that generic
+/// middleware can inspect kinds regardless of the concrete error type.
+///
+/// Unlike [`IoErrorExt`](crate::IoErrorExt), this trait is not sealed, so may be
+/// implemented for application error types.
+///
+/// ```
+/// use std::{error::Error, io};
+/// use io_extra::AsIoError;
+///
+/// fn is_retryable(e: &impl AsIoError) -> bool {
+///     e.as_io().is_some_and(|it| it.kind() == io::ErrorKind::TimedOut)
+/// }
+///
+/// assert!(is_retryable(&io::Error::from(io::ErrorKind::TimedOut)));
+///
+/// let boxed: Box<dyn Error> = Box::new(io::Error::from(io::ErrorKind::TimedOut));
+/// assert!(is_retryable(&boxed));
+///
+/// let boxed: Box<dyn Error> = Box::from("not io");
+/// assert!(!is_retryable(&boxed));
+/// ```
+pub trait AsIoError {
+    /// The underlying [`io::Error`], if this is one.
+    fn as_io(&self) -> Option<&io::Error>;
+}
+
+impl AsIoError for io::Error {
+    fn as_io(&self) -> Option<&io::Error> {
+        Some(self)
+    }
+}
+
+impl<T: AsIoError + ?Sized> AsIoError for &T {
+    fn as_io(&self) -> Option<&io::Error> {
+        T::as_io(self)
+    }
+}
+
+impl AsIoError for dyn Error + 'static {
+    fn as_io(&self) -> Option<&io::Error> {
+        self.downcast_ref()
+    }
+}
+
+impl AsIoError for dyn Error + Send + Sync + 'static {
+    fn as_io(&self) -> Option<&io::Error> {
+        self.downcast_ref()
+    }
+}
+
+impl<T: AsIoError + ?Sized> AsIoError for Box<T> {
+    fn as_io(&self) -> Option<&io::Error> {
+        T::as_io(self)
+    }
+}
+
+impl AsIoError for crate::MultiError {
+    /// The [representative](crate::MultiError::representative) error.
+    fn as_io(&self) -> Option<&io::Error> {
+        self.representative()
+    }
+}
+
+/// ```
+/// use std::io;
+/// use io_extra::AsIoError;
+///
+/// let e = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound)).context("loading");
+/// assert_eq!(e.as_io().map(io::Error::kind), Some(io::ErrorKind::NotFound));
+/// ```
+#[cfg(feature = "anyhow")]
+impl AsIoError for anyhow::Error {
+    fn as_io(&self) -> Option<&io::Error> {
+        self.downcast_ref()
+    }
+}
+
+/// ```
+/// use std::io;
+/// use io_extra::AsIoError;
+///
+/// let e = eyre::Report::new(io::Error::from(io::ErrorKind::NotFound)).wrap_err("loading");
+/// assert_eq!(e.as_io().map(io::Error::kind), Some(io::ErrorKind::NotFound));
+/// ```
+#[cfg(feature = "eyre")]
+impl AsIoError for eyre::Report {
+    fn as_io(&self) -> Option<&io::Error> {
+        self.downcast_ref()
+    }
+}
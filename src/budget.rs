@@ -0,0 +1,71 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        OnceLock,
+    },
+    time::Instant,
+};
+
+const UNLIMITED: u64 = u64::MAX;
+
+static LIMIT: AtomicU64 = AtomicU64::new(UNLIMITED);
+static WINDOW: AtomicU64 = AtomicU64::new(0);
+static USED: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED: AtomicU64 = AtomicU64::new(0);
+
+/// Limit the number of context frames allocated per second, process-wide.
+///
+/// Beyond the limit, [`context()`](crate::context()) (and everything built on
+/// it) returns the original error unchanged, without formatting the message,
+/// protecting latency-sensitive services from error storms.
+/// Suppressed frames are counted in [`suppressed_contexts`].
+///
+/// `None` (the default) removes the limit.
+/// The accounting is approximate under contention.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{context, set_context_budget, suppressed_contexts};
+///
+/// set_context_budget(Some(0));
+/// let e = context(io::Error::other("boom"), "frobnicating");
+/// assert_eq!(e.to_string(), "boom");
+/// assert_eq!(suppressed_contexts(), 1);
+///
+/// set_context_budget(None);
+/// let e = context(io::Error::other("boom"), "frobnicating");
+/// assert_eq!(e.to_string(), "frobnicating");
+/// ```
+pub fn set_context_budget(per_second: Option<u32>) {
+    LIMIT.store(per_second.map(u64::from).unwrap_or(UNLIMITED), Relaxed)
+}
+
+/// The number of context frames skipped because of [`set_context_budget`].
+pub fn suppressed_contexts() -> u64 {
+    SUPPRESSED.load(Relaxed)
+}
+
+/// Returns `false` if the budget for this second is exhausted.
+pub(crate) fn acquire() -> bool {
+    let limit = LIMIT.load(Relaxed);
+    if limit == UNLIMITED {
+        return true;
+    }
+    static START: OnceLock<Instant> = OnceLock::new();
+    let now = START.get_or_init(Instant::now).elapsed().as_secs();
+    let window = WINDOW.load(Relaxed);
+    if now != window
+        && WINDOW
+            .compare_exchange(window, now, Relaxed, Relaxed)
+            .is_ok()
+    {
+        USED.store(0, Relaxed)
+    }
+    match USED.fetch_add(1, Relaxed) < limit {
+        true => true,
+        false => {
+            SUPPRESSED.fetch_add(1, Relaxed);
+            false
+        }
+    }
+}
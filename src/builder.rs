@@ -0,0 +1,120 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use crate::context;
+
+/// Builds an [`io::Error`] from a kind, message, source and metadata, in any
+/// order.
+///
+/// The built error's chain is: the `message` (with any fields), then the
+/// `path`, then the `source` (or OS error).
+///
+/// ```
+/// use std::{io, path::Path};
+/// use io_extra::{fields, os_error_code, path, ErrorBuilder};
+///
+/// let e = ErrorBuilder::new()
+///     .message("loading config")
+///     .path("/etc/app.toml")
+///     .os_code(2)
+///     .field("attempt", 3)
+///     .kind(io::ErrorKind::InvalidInput)
+///     .build();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+/// assert!(format!("{:#}", e).starts_with("loading config: /etc/app.toml: "));
+/// assert_eq!(os_error_code(&e), Some(2));
+/// assert_eq!(path(&e), Some(Path::new("/etc/app.toml")));
+/// assert_eq!(fields(&e).collect::<Vec<_>>(), [("attempt", "3")]);
+///
+/// let e = ErrorBuilder::new().kind(io::ErrorKind::TimedOut).message("no reply").build();
+/// assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+/// assert_eq!(e.to_string(), "no reply");
+/// ```
+#[derive(Debug, Default)]
+pub struct ErrorBuilder {
+    kind: Option<ErrorKind>,
+    message: Option<String>,
+    source: Option<Box<dyn Error + Send + Sync>>,
+    os_code: Option<i32>,
+    fields: Vec<(String, String)>,
+    path: Option<PathBuf>,
+}
+
+impl ErrorBuilder {
+    /// Start building an error.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the kind.
+    ///
+    /// Otherwise, the kind comes from the source or OS error, or is
+    /// [`ErrorKind::Other`].
+    pub fn kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+    /// Set the outermost message.
+    pub fn message(mut self, message: impl fmt::Display) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+    /// Set the underlying cause.
+    ///
+    /// The source is converted as with [`into_io`](crate::into_io).
+    pub fn source(mut self, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+    /// Make the underlying cause an OS error with `code`, replacing any
+    /// [`source`](Self::source).
+    pub fn os_code(mut self, code: i32) -> Self {
+        self.os_code = Some(code);
+        self
+    }
+    /// Add a structured [field](crate::fields()).
+    pub fn field(mut self, key: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+    /// Set the [path](crate::path()) the error concerns.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+    /// Build the error.
+    pub fn build(self) -> io::Error {
+        let cause = match (self.os_code, self.source) {
+            (Some(code), _) => Some(io::Error::from_raw_os_error(code)),
+            (None, Some(source)) => Some(crate::classify::into_io_boxed(source)),
+            (None, None) => None,
+        };
+        let cause = match (cause, self.path) {
+            (Some(cause), Some(path)) => Some(context::with_path(cause, path)),
+            (None, Some(path)) => Some(context::with_path(
+                io::Error::from(self.kind.unwrap_or(ErrorKind::Other)),
+                path,
+            )),
+            (cause, None) => cause,
+        };
+        let e = match (cause, self.message) {
+            (Some(cause), Some(message)) => context::context_fields(cause, message, self.fields),
+            (Some(cause), None) => context::with_fields(cause, self.fields),
+            (None, message) => {
+                let kind = self.kind.unwrap_or(ErrorKind::Other);
+                let e = match message {
+                    Some(message) => io::Error::new(kind, message),
+                    None => io::Error::from(kind),
+                };
+                context::with_fields(e, self.fields)
+            }
+        };
+        match self.kind {
+            Some(kind) => context::with_kind(e, kind),
+            None => e,
+        }
+    }
+}
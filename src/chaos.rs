@@ -0,0 +1,183 @@
+//! Opt-in failure injection for validating error handling end-to-end.
+//!
+//! Failures are configured by the `IO_EXTRA_CHAOS` environment variable, a
+//! comma-separated list of `operation:probability:kind` rules:
+//!
+//! ```text
+//! IO_EXTRA_CHAOS=write:0.01:ConnectionReset,read:0.001:TimedOut
+//! ```
+//!
+//! An operation of `*` matches every operation.
+//! The variable is read once, on the first [`check`], which panics if it is malformed.
+//!
+//! Failures are drawn from a seeded generator, and each injected error records
+//! the seed and its sequence number.
+//! Setting `IO_EXTRA_CHAOS_SEED` to a recorded seed replays the same sequence of
+//! decisions, so a failing run can be reproduced exactly (provided operations
+//! happen in the same order).
+//!
+//! ```
+//! use std::io::{self, Write as _};
+//! use io_extra::chaos::Chaos;
+//!
+//! std::env::set_var("IO_EXTRA_CHAOS", "write:1:ConnectionReset");
+//!
+//! let mut w = Chaos::new(Vec::new());
+//! let e = w.write(b"hello").unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+//! assert!(e.to_string().contains("IO_EXTRA_CHAOS_SEED="));
+//! w.flush().unwrap();
+//! ```
+
+use std::{
+    collections::hash_map::RandomState,
+    env,
+    hash::BuildHasher as _,
+    io::{self, ErrorKind},
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        OnceLock,
+    },
+};
+
+const VAR: &str = "IO_EXTRA_CHAOS";
+const SEED_VAR: &str = "IO_EXTRA_CHAOS_SEED";
+
+#[derive(Debug)]
+struct Rule {
+    operation: String,
+    probability: f64,
+    kind: ErrorKind,
+}
+
+fn parse(spec: &str) -> Result<Vec<Rule>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+        .map(|rule| {
+            let mut parts = rule.split(':');
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(operation), Some(probability), Some(kind), None) => Ok(Rule {
+                    operation: operation.into(),
+                    probability: probability
+                        .parse()
+                        .map_err(|e| format!("bad probability in `{}`: {}", rule, e))?,
+                    kind: crate::kind::from_name(kind)
+                        .ok_or_else(|| format!("unknown error kind in `{}`", rule))?,
+                }),
+                _ => Err(format!(
+                    "expected `operation:probability:kind`, got `{}`",
+                    rule
+                )),
+            }
+        })
+        .collect()
+}
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| match env::var(VAR) {
+        Ok(spec) => parse(&spec).unwrap_or_else(|e| panic!("invalid {}: {}", VAR, e)),
+        Err(_) => vec![],
+    })
+}
+
+/// The seed failures are drawn from.
+///
+/// This is `IO_EXTRA_CHAOS_SEED` if set, or chosen at random otherwise.
+pub fn seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(|| match env::var(SEED_VAR) {
+        Ok(seed) => seed
+            .trim()
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid {}: {}", SEED_VAR, e)),
+        Err(_) => RandomState::new().hash_one(0),
+    })
+}
+
+/// [SplitMix64](https://prng.di.unimi.it/splitmix64.c), used as a hash of
+/// `(seed, sequence)` so that every decision is independently reproducible.
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A uniformly distributed number in `[0, 1)`, for the given `sequence` number.
+fn random(sequence: u64) -> f64 {
+    let bits = splitmix64(seed() ^ splitmix64(sequence));
+    (bits >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Consult the configured rules for `operation`, returning an injected error if
+/// one fires.
+///
+/// This is what [`Chaos`] calls before each operation, and may be used to add
+/// injection points to other code.
+pub fn check(operation: &str) -> io::Result<()> {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    for rule in rules() {
+        if rule.operation == "*" || rule.operation == operation {
+            let sequence = SEQUENCE.fetch_add(1, Relaxed);
+            if random(sequence) < rule.probability {
+                return Err(io::Error::new(
+                    rule.kind,
+                    format!(
+                        "chaos: injected {} failure (sequence {}, reproduce with {}={})",
+                        operation,
+                        sequence,
+                        SEED_VAR,
+                        seed()
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An adapter which injects failures into the `read`, `write` and `flush`
+/// operations of the inner stream, according to the module configuration.
+#[derive(Debug, Clone, Default)]
+pub struct Chaos<T> {
+    inner: T,
+}
+
+impl<T> Chaos<T> {
+    /// Wrap `inner`.
+    pub const fn new(inner: T) -> Self {
+        Self { inner }
+    }
+    /// Get a reference to the inner stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+    /// Unwrap the inner stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: io::Read> io::Read for Chaos<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        check("read")?;
+        self.inner.read(buf)
+    }
+}
+
+impl<T: io::Write> io::Write for Chaos<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        check("write")?;
+        self.inner.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        check("flush")?;
+        self.inner.flush()
+    }
+}
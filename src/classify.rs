@@ -0,0 +1,354 @@
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+    sync::RwLock,
+};
+
+use crate::context::Chain;
+
+type Classify = dyn Fn(&(dyn Error + 'static)) -> Option<ErrorKind> + Send + Sync;
+
+/// Decides the [`ErrorKind`] of an otherwise opaque error.
+///
+/// Register classifiers with [`register_classifier`].
+pub struct Classifier(Box<Classify>);
+
+impl Classifier {
+    /// Classify errors by a predicate.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> Option<ErrorKind> + Send + Sync + 'static,
+    {
+        Self(Box::new(f))
+    }
+    /// Classify every error of type `E` as `kind`.
+    pub fn by_type<E: Error + 'static>(kind: ErrorKind) -> Self {
+        Self::new(move |e| match e.is::<E>() {
+            true => Some(kind),
+            false => None,
+        })
+    }
+    /// Classify errors by recognising common phrases in their messages, with
+    /// [`classify_message`].
+    ///
+    /// This is not registered by default.
+    pub fn by_message() -> Self {
+        Self::new(|e| classify_message(&e.to_string()))
+    }
+}
+
+impl fmt::Debug for Classifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Classifier").finish_non_exhaustive()
+    }
+}
+
+static CLASSIFIERS: RwLock<Vec<Classifier>> = RwLock::new(Vec::new());
+
+/// Teach this crate how to classify an error type, process-wide.
+///
+/// Classifiers are consulted in the order they are registered, by
+/// [`reclassify_other`], [`into_io`], and `from_anyhow`.
+pub fn register_classifier(classifier: Classifier) {
+    CLASSIFIERS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(classifier)
+}
+
+/// Consult the [registered](register_classifier) classifiers for each error in
+/// the chain starting at `root`.
+pub fn classify(root: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    let classifiers = CLASSIFIERS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    Chain::new(Some(root)).find_map(|e| classifiers.iter().find_map(|it| (it.0)(e)))
+}
+
+/// Guess the [`ErrorKind`] of an error which only arrived as text, e.g by
+/// parsing a subprocess's stderr, or over a legacy protocol.
+///
+/// Common phrases from POSIX `strerror`, Windows, and the standard library are
+/// recognised, case-insensitively.
+///
+/// ```
+/// use std::io;
+/// use io_extra::classify_message;
+///
+/// assert_eq!(
+///     classify_message("rsync: write failed: Connection reset by peer (104)"),
+///     Some(io::ErrorKind::ConnectionReset),
+/// );
+/// assert_eq!(classify_message("Access is denied."), Some(io::ErrorKind::PermissionDenied));
+/// assert_eq!(classify_message("the frobnicator is sad"), None);
+/// ```
+pub fn classify_message(message: &str) -> Option<ErrorKind> {
+    const PHRASES: &[(&str, ErrorKind)] = &[
+        ("connection reset", ErrorKind::ConnectionReset),
+        ("connection refused", ErrorKind::ConnectionRefused),
+        ("connection aborted", ErrorKind::ConnectionAborted),
+        ("broken pipe", ErrorKind::BrokenPipe),
+        ("address already in use", ErrorKind::AddrInUse),
+        (
+            "cannot assign requested address",
+            ErrorKind::AddrNotAvailable,
+        ),
+        ("not connected", ErrorKind::NotConnected),
+        ("permission denied", ErrorKind::PermissionDenied),
+        ("access denied", ErrorKind::PermissionDenied),
+        ("access is denied", ErrorKind::PermissionDenied),
+        ("operation not permitted", ErrorKind::PermissionDenied),
+        ("no such file or directory", ErrorKind::NotFound),
+        ("cannot find the", ErrorKind::NotFound),
+        ("not found", ErrorKind::NotFound),
+        ("does not exist", ErrorKind::NotFound),
+        ("file exists", ErrorKind::AlreadyExists),
+        ("already exists", ErrorKind::AlreadyExists),
+        ("timed out", ErrorKind::TimedOut),
+        ("timeout", ErrorKind::TimedOut),
+        ("resource temporarily unavailable", ErrorKind::WouldBlock),
+        ("would block", ErrorKind::WouldBlock),
+        ("interrupted system call", ErrorKind::Interrupted),
+        ("unexpected end of file", ErrorKind::UnexpectedEof),
+        ("unexpected eof", ErrorKind::UnexpectedEof),
+        ("out of memory", ErrorKind::OutOfMemory),
+        ("cannot allocate memory", ErrorKind::OutOfMemory),
+        ("invalid argument", ErrorKind::InvalidInput),
+        ("not supported", ErrorKind::Unsupported),
+        ("not implemented", ErrorKind::Unsupported),
+    ];
+    let message = message.to_lowercase();
+    PHRASES
+        .iter()
+        .find_map(|(phrase, kind)| message.contains(phrase).then_some(*kind))
+}
+
+/// Promote an [`ErrorKind::Other`] error to a more specific kind, by inspecting
+/// its payload.
+///
+/// `f` is called on each error in the payload's chain, followed by any
+/// [registered](register_classifier) classifiers.
+/// If any return a kind, the error is rebuilt with that kind, preserving its payload.
+///
+/// ```
+/// use std::{fmt, io};
+/// use io_extra::{reclassify_other, register_classifier, Classifier, IoErrorExt as _};
+///
+/// #[derive(Debug)]
+/// struct Throttled;
+/// impl fmt::Display for Throttled {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("throttled")
+///     }
+/// }
+/// impl std::error::Error for Throttled {}
+///
+/// register_classifier(Classifier::by_type::<Throttled>(io::ErrorKind::WouldBlock));
+///
+/// let e = reclassify_other(io::Error::other(Throttled).context("uploading"), |_| None);
+/// assert_eq!(e.kind(), io::ErrorKind::WouldBlock);
+/// assert_eq!(format!("{:#}", e), "uploading: throttled");
+///
+/// let e = reclassify_other(io::Error::other("no such bucket"), |e| {
+///     e.to_string().starts_with("no such").then_some(io::ErrorKind::NotFound)
+/// });
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// ```
+pub fn reclassify_other(
+    e: io::Error,
+    f: impl Fn(&(dyn Error + 'static)) -> Option<ErrorKind>,
+) -> io::Error {
+    if e.kind() != ErrorKind::Other {
+        return e;
+    }
+    let kind = e.get_ref().and_then(|payload| {
+        let payload = payload as &(dyn Error + 'static);
+        Chain::new(Some(payload))
+            .find_map(&f)
+            .or_else(|| classify(payload))
+    });
+    match kind {
+        Some(kind) => io::Error::new(kind, e.into_inner().expect("kind came from the payload")),
+        None => e,
+    }
+}
+
+static PRIORITIES: RwLock<Vec<(ErrorKind, u8)>> = RwLock::new(Vec::new());
+
+/// How severe an error of `kind` is, for choosing the representative of
+/// several failures, such as the kind of a [`MultiError`](crate::MultiError).
+///
+/// Higher is more severe.
+/// By default, problems a retry won't fix outrank transient ones:
+///
+/// | Priority | Kinds |
+/// | -------- | ----- |
+/// | 100 | [`OutOfMemory`](ErrorKind::OutOfMemory), [`InvalidData`](ErrorKind::InvalidData), and running out of storage |
+/// | 80 | [`PermissionDenied`](ErrorKind::PermissionDenied), [`InvalidInput`](ErrorKind::InvalidInput), [`Unsupported`](ErrorKind::Unsupported) |
+/// | 60 | [`NotFound`](ErrorKind::NotFound), [`AlreadyExists`](ErrorKind::AlreadyExists), addresses, and filesystem structure |
+/// | 40 | Connections failing, and streams ending early |
+/// | 20 | [`TimedOut`](ErrorKind::TimedOut), and busy resources |
+/// | 10 | [`WouldBlock`](ErrorKind::WouldBlock), [`Interrupted`](ErrorKind::Interrupted) |
+/// | 0 | [`Other`](ErrorKind::Other), which carries no information, and unknown kinds |
+///
+/// Overrides from [`set_kind_priority`] take precedence.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{kind_priority, set_kind_priority};
+///
+/// assert!(kind_priority(io::ErrorKind::PermissionDenied) > kind_priority(io::ErrorKind::TimedOut));
+///
+/// set_kind_priority(io::ErrorKind::TimedOut, 255);
+/// assert_eq!(kind_priority(io::ErrorKind::TimedOut), 255);
+/// ```
+pub fn kind_priority(kind: ErrorKind) -> u8 {
+    let overridden = PRIORITIES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find_map(|(it, priority)| (*it == kind).then_some(*priority));
+    if let Some(priority) = overridden {
+        return priority;
+    }
+    use ErrorKind::*;
+    match kind {
+        OutOfMemory | InvalidData => 100,
+        #[cfg(io_error_more)]
+        StorageFull | FileTooLarge | Deadlock => 100,
+        #[cfg(io_error_quota_exceeded)]
+        QuotaExceeded => 100,
+        PermissionDenied | InvalidInput | Unsupported => 80,
+        #[cfg(io_error_more)]
+        ReadOnlyFilesystem | ArgumentListTooLong => 80,
+        #[cfg(io_error_invalid_filename)]
+        InvalidFilename => 80,
+        NotFound | AlreadyExists | AddrInUse | AddrNotAvailable => 60,
+        #[cfg(io_error_more)]
+        IsADirectory
+        | NotADirectory
+        | DirectoryNotEmpty
+        | NotSeekable
+        | TooManyLinks
+        | StaleNetworkFileHandle => 60,
+        #[cfg(io_error_crosses_devices)]
+        CrossesDevices => 60,
+        ConnectionRefused | ConnectionReset | ConnectionAborted | NotConnected | BrokenPipe
+        | UnexpectedEof | WriteZero => 40,
+        #[cfg(io_error_more)]
+        HostUnreachable | NetworkDown | NetworkUnreachable => 40,
+        TimedOut => 20,
+        #[cfg(io_error_more)]
+        ResourceBusy => 20,
+        WouldBlock | Interrupted => 10,
+        _ => 0,
+    }
+}
+
+/// Override the [priority](kind_priority) of `kind`, process-wide.
+pub fn set_kind_priority(kind: ErrorKind, priority: u8) {
+    let mut priorities = PRIORITIES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    priorities.retain(|(it, _)| *it != kind);
+    priorities.push((kind, priority))
+}
+
+/// Convert any error into an [`io::Error`], choosing its kind by:
+/// - the [registered](register_classifier) classifiers,
+/// - the kind of the first [`io::Error`] in its chain,
+/// - otherwise, [`ErrorKind::Other`].
+///
+/// [`io::Error`]s are returned unchanged.
+///
+/// ```
+/// use std::io;
+/// use io_extra::into_io;
+///
+/// let e = into_io("nope".parse::<u8>().unwrap_err());
+/// assert_eq!(e.kind(), io::ErrorKind::Other);
+///
+/// let e = into_io(io::Error::from(io::ErrorKind::TimedOut));
+/// assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+/// ```
+pub fn into_io<E: Error + Send + Sync + 'static>(e: E) -> io::Error {
+    into_io_boxed(Box::new(e))
+}
+
+/// See [`into_io`].
+pub(crate) fn into_io_boxed(e: Box<dyn Error + Send + Sync>) -> io::Error {
+    match e.downcast::<io::Error>() {
+        Ok(e) => *e,
+        Err(e) => {
+            let kind = kind_of(&*e);
+            io::Error::new(kind, e)
+        }
+    }
+}
+
+/// Convert an error which can never happen into an [`io::Error`].
+///
+/// This lets code which is generic over its error type, where that type may be
+/// [`Infallible`], compose with [`io::Result`]s, without going through [`into_io`].
+///
+/// ```
+/// use std::{convert::Infallible, io};
+/// use io_extra::absurd_io;
+///
+/// fn parse<E>(s: &str, f: impl Fn(&str) -> Result<u32, E>) -> Result<u32, E> {
+///     f(s)
+/// }
+///
+/// let n: io::Result<u32> = parse("4", |s| Ok::<_, Infallible>(s.len() as u32)).map_err(absurd_io);
+/// assert_eq!(n?, 1);
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn absurd_io(never: Infallible) -> io::Error {
+    match never {}
+}
+
+/// Convert a [`Result`] which can never fail into an [`io::Result`].
+///
+/// See [`absurd_io`].
+///
+/// ```
+/// use std::{convert::Infallible, io};
+/// use io_extra::infallible_io;
+///
+/// let res: io::Result<u8> = infallible_io(Ok::<_, Infallible>(1));
+/// assert_eq!(res?, 1);
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn infallible_io<T>(res: Result<T, Infallible>) -> io::Result<T> {
+    res.map_err(absurd_io)
+}
+
+fn kind_of(e: &(dyn Error + 'static)) -> ErrorKind {
+    classify(e)
+        .or_else(|| {
+            Chain::new(Some(e)).find_map(|it| it.downcast_ref::<io::Error>().map(io::Error::kind))
+        })
+        .unwrap_or(ErrorKind::Other)
+}
+
+/// Convert an [`anyhow::Error`] into an [`io::Error`], as in [`into_io`].
+///
+/// ```
+/// use std::io;
+/// use io_extra::from_anyhow;
+///
+/// let e = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound)).context("loading");
+/// assert_eq!(from_anyhow(e).kind(), io::ErrorKind::NotFound);
+/// ```
+#[cfg(feature = "anyhow")]
+pub fn from_anyhow(e: anyhow::Error) -> io::Error {
+    match e.downcast::<io::Error>() {
+        Ok(e) => e,
+        Err(e) => {
+            let kind = kind_of(e.as_ref());
+            io::Error::new(kind, e)
+        }
+    }
+}
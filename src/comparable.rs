@@ -0,0 +1,90 @@
+use std::{
+    fmt,
+    io::{self, ErrorKind},
+};
+
+use crate::display::Headline;
+
+/// A snapshot of an [`io::Error`] which may be compared, hashed, and used as a
+/// map key, for deduplicating and aggregating errors.
+///
+/// Created by [`IoErrorExt::comparable`](crate::IoErrorExt::comparable).
+///
+/// ```
+/// use std::{collections::HashMap, io};
+/// use io_extra::IoErrorExt as _;
+///
+/// let errors = [
+///     io::Error::from_raw_os_error(2).context("opening  config"),
+///     io::Error::from_raw_os_error(2).context("opening config"),
+///     io::Error::permission_denied("no").context("opening config"),
+/// ];
+/// let mut counts = HashMap::new();
+/// for e in &errors {
+///     *counts.entry(e.comparable()).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts.len(), 2);
+/// assert_eq!(counts[&errors[0].comparable()], 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComparableError {
+    kind: ErrorKind,
+    message: String,
+    os_code: Option<i32>,
+}
+
+impl ComparableError {
+    /// The kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+    /// The messages of the error and its causes, joined with `": "`, with runs of
+    /// whitespace collapsed, and without OS error codes.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// The OS error code in the chain, if any (see [`os_error_code`](crate::os_error_code)).
+    pub fn os_code(&self) -> Option<i32> {
+        self.os_code
+    }
+}
+
+impl From<&io::Error> for ComparableError {
+    fn from(e: &io::Error) -> Self {
+        let mut message = String::new();
+        for (ix, cause) in crate::chain(e).enumerate() {
+            if ix != 0 {
+                message.push_str(": ")
+            }
+            let cause = match cause.downcast_ref::<io::Error>() {
+                Some(it) => Headline(it).to_string(),
+                None => cause.to_string(),
+            };
+            for (ix, word) in cause.split_whitespace().enumerate() {
+                if ix != 0 {
+                    message.push(' ')
+                }
+                message.push_str(word)
+            }
+        }
+        Self {
+            kind: e.kind(),
+            message,
+            os_code: crate::os_error_code(e),
+        }
+    }
+}
+
+/// The [`message`](ComparableError::message).
+impl fmt::Display for ComparableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// An error with the same kind and message as the snapshot.
+impl From<ComparableError> for io::Error {
+    fn from(e: ComparableError) -> Self {
+        io::Error::new(e.kind, e.message)
+    }
+}
@@ -1,4 +1,10 @@
-use std::{error::Error, fmt, io, iter};
+use std::{
+    any::Any,
+    error::Error,
+    fmt, io, iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// Lazily attach a message to an [`io::Error`].
 /// This is particularly useful combined with [`Result::map_err`].
@@ -20,8 +26,269 @@ pub fn with(context: impl fmt::Display) -> impl FnOnce(io::Error) -> io::Error {
 ///
 /// This is provided as a free function to not conflict with [`anyhow::Context`]
 ///
+/// See [`set_context_budget`](crate::set_context_budget) for limiting the rate
+/// at which context is attached.
+///
 /// [`anyhow::Context`]: (https://docs.rs/anyhow/1/anyhow/trait.Context.html#method.context).
 pub fn context(e: io::Error, context: impl fmt::Display) -> io::Error {
+    lazy_context(e, || context)
+}
+
+/// Attach a message to an [`io::Error`], only building it if the
+/// [budget](crate::set_context_budget) allows.
+pub(crate) fn lazy_context<D: fmt::Display>(e: io::Error, f: impl FnOnce() -> D) -> io::Error {
+    if !crate::budget::acquire() {
+        return e;
+    }
+    let message = f().to_string();
+    crate::lint::check(&e, &message);
+    let (kind, frame) = Context::wrap(e, Some(Message::new(message)));
+    io::Error::new(kind, frame)
+}
+
+/// Attach a shared message to this [`io::Error`], without copying it.
+///
+/// [`context`] formats a fresh [`String`] for every frame.
+/// When the same message (a request ID, or a resource name) is attached to many
+/// errors, create it once as an [`Arc<str>`], and each frame will only hold a
+/// reference-counted pointer to it.
+/// Other strings, such as a [`Cow<str>`](std::borrow::Cow), are also accepted,
+/// but are copied into a new [`Arc`].
+///
+/// ```
+/// use std::{io, sync::Arc};
+/// use io_extra::shared_context;
+///
+/// let request_id = Arc::<str>::from("request 6b1f2c");
+/// let errors = (0..1000)
+///     .map(|_| shared_context(io::Error::other("backend unavailable"), request_id.clone()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(format!("{:#}", errors[0]), "request 6b1f2c: backend unavailable");
+///
+/// // Every frame shares the one allocation.
+/// assert_eq!(Arc::strong_count(&request_id), 1001);
+/// ```
+pub fn shared_context(e: io::Error, context: impl Into<Arc<str>>) -> io::Error {
+    if !crate::budget::acquire() {
+        return e;
+    }
+    let (kind, frame) = Context::wrap(e, Some(Message::Shared(context.into())));
+    io::Error::new(kind, frame)
+}
+
+/// Attach a message to an [`io::Error`], changing its kind.
+pub(crate) fn kind_context(
+    e: io::Error,
+    kind: io::ErrorKind,
+    context: impl fmt::Display,
+) -> io::Error {
+    let message = match crate::budget::acquire() {
+        true => {
+            let message = context.to_string();
+            crate::lint::check(&e, &message);
+            Some(Message::new(message))
+        }
+        false => None,
+    };
+    let (_, frame) = Context::wrap(e, message);
+    io::Error::new(kind, frame)
+}
+
+/// Change the kind of `e`, keeping its messages and sources.
+pub(crate) fn with_kind(e: io::Error, kind: io::ErrorKind) -> io::Error {
+    if e.kind() == kind {
+        return e;
+    }
+    match e.get_ref().is_some_and(|it| it.is::<Context>()) {
+        // Reuse the outermost frame.
+        true => io::Error::new(kind, e.into_inner().expect("checked above")),
+        false => {
+            let (_, frame) = Context::wrap(e, None);
+            io::Error::new(kind, frame)
+        }
+    }
+}
+
+/// Attach a message and structured fields to an [`io::Error`].
+#[doc(hidden)]
+pub fn context_fields<K: Into<String>, V: fmt::Display>(
+    e: io::Error,
+    context: impl fmt::Display,
+    fields: impl IntoIterator<Item = (K, V)>,
+) -> io::Error {
+    if !crate::budget::acquire() {
+        return e;
+    }
+    let message = context.to_string();
+    crate::lint::check(&e, &message);
+    let (kind, mut frame) = Context::wrap(e, Some(Message::new(message)));
+    frame.fields = fields
+        .into_iter()
+        .map(|(k, v)| (k.into(), v.to_string()))
+        .collect();
+    io::Error::new(kind, frame)
+}
+
+/// The structured fields attached to an [`io::Error`] and its causes,
+/// outermost first.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{ctxt, fields};
+///
+/// let (src, dst) = ("a.txt", "b.txt");
+/// let e = Err::<(), _>(io::Error::other("disk on fire"))
+///     .map_err(ctxt!("copying {src} to {dst}", src, dst))
+///     .unwrap_err();
+/// assert_eq!(e.to_string(), "copying a.txt to b.txt");
+/// assert_eq!(
+///     fields(&e).collect::<Vec<_>>(),
+///     [("src", "a.txt"), ("dst", "b.txt")],
+/// );
+/// ```
+pub fn fields(e: &io::Error) -> impl Iterator<Item = (&str, &str)> {
+    frames(e).flat_map(|frame| frame.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+}
+
+/// The outermost value of type `T` attached to an [`io::Error`] or its causes
+/// with [`with_payload`](crate::IoErrorExt::with_payload).
+///
+/// ```
+/// use std::io;
+/// use io_extra::{payload_of, IoErrorExt as _};
+///
+/// struct RequestId(u64);
+///
+/// let e = io::Error::other("backend unavailable")
+///     .with_payload(RequestId(42))
+///     .context("handling request");
+/// assert_eq!(payload_of::<RequestId>(&e).map(|it| it.0), Some(42));
+/// assert!(payload_of::<String>(&e).is_none());
+/// ```
+pub fn payload_of<T: Any>(e: &io::Error) -> Option<&T> {
+    frames(e).find_map(|frame| frame.payloads.iter().find_map(|it| it.downcast_ref::<T>()))
+}
+
+/// Attach the path that `e` concerns, as its own frame.
+pub(crate) fn with_path(e: io::Error, path: PathBuf) -> io::Error {
+    path_context(e, path, |path| path.display().to_string())
+}
+
+/// Attach a message built from the path that `e` concerns, recording the path.
+pub(crate) fn path_context(
+    e: io::Error,
+    path: PathBuf,
+    message: impl FnOnce(&Path) -> String,
+) -> io::Error {
+    if !crate::budget::acquire() {
+        return e;
+    }
+    let message = message(&path);
+    crate::lint::check(&e, &message);
+    let (kind, mut frame) = Context::wrap(e, Some(Message::Owned(message)));
+    frame.path = Some(path);
+    io::Error::new(kind, frame)
+}
+
+/// The innermost path [attached](crate::IoErrorExt::with_path) to `e`, i.e the
+/// one closest to the failure.
+///
+/// ```
+/// use std::{fs::File, io, path::Path};
+/// use io_extra::{path, IoErrorExt as _, IoResultExt as _};
+///
+/// let e = File::open("/no/such/file")
+///     .with_path("/no/such/file")
+///     .context("loading config")
+///     .unwrap_err();
+/// assert_eq!(path(&e), Some(Path::new("/no/such/file")));
+/// assert!(format!("{:#}", e).starts_with("loading config: /no/such/file: "));
+///
+/// assert_eq!(path(&io::Error::other("not about a file")), None);
+/// ```
+pub fn path(e: &io::Error) -> Option<&Path> {
+    frames(e).filter_map(|frame| frame.path.as_deref()).last()
+}
+
+/// The innermost path attached to `e`, as in [`path()`], if it is UTF-8.
+///
+/// Paths attached as [`Utf8Path`](camino::Utf8Path)s, which every path-taking
+/// API in this crate accepts, are stored unchanged, so are always recovered.
+///
+/// ```
+/// use std::io;
+/// use camino::Utf8Path;
+/// use io_extra::{fs, utf8_path, IoResultExt as _};
+///
+/// let config = Utf8Path::new("/no/such/config.toml");
+/// let e = fs::read_to_string(config).unwrap_err();
+/// assert_eq!(utf8_path(&e), Some(config));
+///
+/// let e = Err::<(), _>(io::Error::other("corrupt"))
+///     .with_path(config.to_owned())
+///     .unwrap_err();
+/// assert_eq!(utf8_path(&e), Some(config));
+/// ```
+#[cfg(feature = "camino")]
+pub fn utf8_path(e: &io::Error) -> Option<&camino::Utf8Path> {
+    camino::Utf8Path::from_path(path(e)?)
+}
+
+/// Add `fields` to the outermost frame of `e`.
+pub(crate) fn with_fields(e: io::Error, fields: Vec<(String, String)>) -> io::Error {
+    match fields.is_empty() {
+        true => e,
+        false => with_frame(e, |frame| frame.fields.extend(fields)),
+    }
+}
+
+/// Attach `secondary` to `primary`, shown in its [`Debug`](fmt::Debug) representation.
+pub(crate) fn suppress(primary: io::Error, secondary: io::Error) -> io::Error {
+    with_frame(primary, |frame| frame.suppressed.push(secondary))
+}
+
+/// Modify the outermost frame of `e`, adding a transparent one if there is none.
+pub(crate) fn with_frame(mut e: io::Error, f: impl FnOnce(&mut Context)) -> io::Error {
+    if let Some(frame) = e.get_mut().and_then(|it| it.downcast_mut::<Context>()) {
+        f(frame);
+        return e;
+    }
+    let (kind, mut frame) = Context::wrap(e, None);
+    f(&mut frame);
+    io::Error::new(kind, frame)
+}
+
+/// The [`Context`] frames of `e`, outermost first.
+pub(crate) fn frames(e: &io::Error) -> impl Iterator<Item = &Context> {
+    errors(e).filter_map(|it| it.downcast_ref::<Context>())
+}
+
+/// Every error in the chain of `e`, outermost first, including the payloads of
+/// nested [`io::Error`]s and transparent [`Context`]s.
+pub(crate) fn errors(e: &io::Error) -> impl Iterator<Item = &(dyn Error + 'static)> {
+    let mut next = e.get_ref().map(|it| it as &(dyn Error + 'static));
+    iter::from_fn(move || {
+        let current = next?;
+        next = match (
+            current.downcast_ref::<Context>(),
+            current.downcast_ref::<io::Error>(),
+        ) {
+            (Some(frame), _) => frame.source.as_deref().map(|it| it as _),
+            // `io::Error::source` skips its payload.
+            (_, Some(e)) => e.get_ref().map(|it| it as _),
+            (None, None) => current.source(),
+        };
+        Some(current)
+    })
+}
+
+/// Decompose `e` so that it may be wrapped in a new [`Context`].
+fn into_source(
+    e: io::Error,
+) -> (
+    io::ErrorKind,
+    Option<Box<dyn Error + Send + Sync + 'static>>,
+) {
     let kind = e.kind();
     let stringified = e.to_string();
     let source = match (
@@ -38,13 +305,7 @@ pub fn context(e: io::Error, context: impl fmt::Display) -> io::Error {
         // ErrorData::SimpleMessage
         (None, false, None) => Some(Box::new(SimpleMessage(stringified)) as _),
     };
-    io::Error::new(
-        kind,
-        Context {
-            context: context.to_string(),
-            source,
-        },
-    )
+    (kind, source)
 }
 
 #[derive(Debug)]
@@ -56,22 +317,98 @@ impl fmt::Display for SimpleMessage {
 }
 impl Error for SimpleMessage {}
 
+/// The message of a [`Context`] frame.
+#[derive(Debug)]
+enum Message {
+    Owned(String),
+    Shared(Arc<str>),
+}
+impl Message {
+    fn new(s: String) -> Self {
+        match crate::intern::intern(&s) {
+            Some(shared) => Message::Shared(shared),
+            None => Message::Owned(s),
+        }
+    }
+    fn as_str(&self) -> &str {
+        match self {
+            Message::Owned(it) => it,
+            Message::Shared(it) => it,
+        }
+    }
+}
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Owned(it) => it.fmt(f),
+            Message::Shared(it) => it.fmt(f),
+        }
+    }
+}
+
+/// A frame in an error chain.
+///
+/// A frame without a `context` message is transparent, displaying as its `source`.
 #[derive(Debug)]
-struct Context {
-    context: String,
+pub(crate) struct Context {
+    context: Option<Message>,
     source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    suppressed: Vec<io::Error>,
+    fields: Vec<(String, String)>,
+    pub(crate) payloads: Vec<Box<dyn Any + Send + Sync>>,
+    pub(crate) help: Option<String>,
+    pub(crate) hints: Vec<String>,
+    pub(crate) layer: Option<crate::Layer>,
+    path: Option<PathBuf>,
+}
+impl Context {
+    /// The message of this frame, if it isn't transparent.
+    #[cfg_attr(not(feature = "defmt"), allow(dead_code))]
+    pub(crate) fn message(&self) -> Option<&str> {
+        self.context.as_ref().map(Message::as_str)
+    }
+    /// Wrap `e` in a new frame.
+    fn wrap(e: io::Error, context: Option<Message>) -> (io::ErrorKind, Self) {
+        if e.get_ref().is_none_or(|it| !it.is::<Context>()) {
+            crate::stats::record(e.kind())
+        }
+        let (kind, source) = match (into_source(e), &context) {
+            // A transparent frame must display something.
+            ((kind, None), None) => (kind, Some(Box::new(SimpleMessage(kind.to_string())) as _)),
+            ((kind, source), _) => (kind, source),
+        };
+        (
+            kind,
+            Self {
+                context,
+                source,
+                suppressed: vec![],
+                fields: vec![],
+                payloads: vec![],
+                help: None,
+                hints: vec![],
+                layer: None,
+                path: None,
+            },
+        )
+    }
 }
 impl Error for Context {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        match &self.source {
-            Some(it) => Some(it.as_ref()),
-            None => None,
+        match (&self.context, &self.source) {
+            (Some(_), Some(it)) => Some(it.as_ref()),
+            (None, Some(it)) => it.source(),
+            (_, None) => None,
         }
     }
 }
 impl fmt::Display for Context {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.context.fmt(f)?;
+        match (&self.context, &self.source) {
+            (Some(context), _) => context.fmt(f)?,
+            (None, Some(source)) => write!(f, "{}", source)?,
+            (None, None) => {}
+        }
         if f.alternate() {
             for parent in Chain::new(self.source()) {
                 write!(f, ": {}", parent)?
@@ -81,15 +418,20 @@ impl fmt::Display for Context {
     }
 }
 
-/// An iterator of [`Error::source`]s.
-#[derive(Debug)]
-struct Chain<'a> {
+/// An iterator over an error and its [`source`](Error::source)s, outermost first.
+///
+/// Created by [`chain`].
+#[derive(Debug, Clone)]
+pub struct Chain<'a> {
     #[allow(clippy::type_complexity)]
-    inner: iter::Successors<&'a dyn Error, fn(&&'a dyn Error) -> Option<&'a dyn Error>>,
+    inner: iter::Successors<
+        &'a (dyn Error + 'static),
+        fn(&&'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)>,
+    >,
 }
 
 impl<'a> Chain<'a> {
-    fn new(root: Option<&'a dyn Error>) -> Self {
+    pub(crate) fn new(root: Option<&'a (dyn Error + 'static)>) -> Self {
         Self {
             inner: iter::successors(root, |e| (*e).source()),
         }
@@ -97,9 +439,188 @@ impl<'a> Chain<'a> {
 }
 
 impl<'a> Iterator for Chain<'a> {
-    type Item = &'a dyn Error;
+    type Item = &'a (dyn Error + 'static);
 
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next()
     }
 }
+
+impl iter::FusedIterator for Chain<'_> {}
+
+/// Iterate over `e` and its [`source`](Error::source)s, outermost first.
+///
+/// Each item may be downcast, to inspect the underlying errors.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{chain, IoErrorExt as _};
+///
+/// let e = io::Error::not_found("no such file")
+///     .context("reading config")
+///     .context("starting up");
+/// let messages = chain(&e).map(|it| it.to_string()).collect::<Vec<_>>();
+/// assert_eq!(messages, ["starting up", "reading config", "no such file"]);
+/// ```
+pub fn chain(e: &io::Error) -> Chain<'_> {
+    Chain::new(Some(e))
+}
+
+/// The innermost error in the chain of `e`, past any context, to match on the
+/// original failure.
+///
+/// This looks inside the payloads of nested [`io::Error`]s, which
+/// [`Error::source`] skips.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{root_cause, IoErrorExt as _};
+///
+/// let e = io::Error::from_raw_os_error(2)
+///     .context("opening config")
+///     .context("starting up");
+/// let root = root_cause(&e).downcast_ref::<io::Error>().unwrap();
+/// assert_eq!(root.raw_os_error(), Some(2));
+/// ```
+pub fn root_cause(e: &io::Error) -> &(dyn Error + 'static) {
+    errors(e).last().unwrap_or(e)
+}
+
+/// The OS error code of `e`, or of the first error in its chain which has one.
+///
+/// [`io::Error::raw_os_error`] returns [`None`] once [`context`] has been
+/// attached, which breaks code matching on `errno`s like `EAGAIN` or `ENOSPC`.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{os_error_code, IoErrorExt as _};
+///
+/// let e = io::Error::from_raw_os_error(28).context("writing journal");
+/// assert_eq!(e.raw_os_error(), None);
+/// assert_eq!(os_error_code(&e), Some(28));
+///
+/// assert_eq!(os_error_code(&io::Error::other("not from the OS")), None);
+/// ```
+pub fn os_error_code(e: &io::Error) -> Option<i32> {
+    e.raw_os_error()
+        .or_else(|| errors(e).find_map(|it| it.downcast_ref::<io::Error>()?.raw_os_error()))
+}
+
+/// Find the first error of type `E` in the chain of `e`, looking through any
+/// context.
+///
+/// [`io::Error::get_ref`] only sees the outermost layer once
+/// [`context`] has been applied.
+///
+/// ```
+/// use std::{fmt, io};
+/// use io_extra::{downcast_source, IoErrorExt as _};
+///
+/// #[derive(Debug)]
+/// struct Corrupt(u64);
+/// impl fmt::Display for Corrupt {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "corrupt block {}", self.0)
+///     }
+/// }
+/// impl std::error::Error for Corrupt {}
+///
+/// let e = io::Error::new(io::ErrorKind::InvalidData, Corrupt(7))
+///     .context("reading index")
+///     .context("opening database");
+/// assert!(e.get_ref().unwrap().downcast_ref::<Corrupt>().is_none());
+/// assert_eq!(downcast_source::<Corrupt>(&e).map(|it| it.0), Some(7));
+/// ```
+pub fn downcast_source<E: Error + 'static>(e: &io::Error) -> Option<&E> {
+    errors(e).find_map(|it| it.downcast_ref::<E>())
+}
+
+/// Take ownership of the first error of type `E` in the chain of `e`, looking
+/// through any context.
+///
+/// Returns `e` unchanged if there is no such error, or if it is only reachable
+/// through an error's [`source`](Error::source), which cannot be taken by value.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{try_downcast_source, IoErrorExt as _};
+///
+/// let e = io::Error::other("boom")
+///     .context("in the middle")
+///     .context("at the top");
+/// let e = try_downcast_source::<std::num::ParseIntError>(e).unwrap_err();
+/// assert_eq!(format!("{:#}", e), "at the top: in the middle: boom");
+///
+/// let inner = io::Error::from_raw_os_error(2);
+/// let e = inner.context("opening config");
+/// let inner = try_downcast_source::<io::Error>(e).unwrap();
+/// assert_eq!(inner.raw_os_error(), Some(2));
+/// ```
+pub fn try_downcast_source<E: Error + Send + Sync + 'static>(e: io::Error) -> Result<E, io::Error> {
+    let reachable = errors(&e)
+        .scan(true, |owned, it| match *owned {
+            true => {
+                *owned = it.is::<Context>() || it.is::<io::Error>();
+                Some(it)
+            }
+            false => None,
+        })
+        .any(|it| it.is::<E>());
+    if !reachable {
+        return Err(e);
+    }
+    let mut current = e.into_inner().expect("checked above");
+    loop {
+        current = match current.downcast::<E>() {
+            Ok(it) => return Ok(*it),
+            Err(it) => it,
+        };
+        current = match current.downcast::<Context>() {
+            Ok(frame) => frame.source.expect("checked above"),
+            Err(it) => match it.downcast::<io::Error>() {
+                Ok(e) => e.into_inner().expect("checked above"),
+                Err(_) => unreachable!("checked above"),
+            },
+        };
+    }
+}
+
+/// Attach a message to `e` with `fields`, which are also rendered after it, as
+/// `message (k=v, k2=v2)`.
+pub(crate) fn displayed_fields<K: Into<String>, V: fmt::Display>(
+    e: io::Error,
+    context: impl fmt::Display,
+    fields: impl IntoIterator<Item = (K, V)>,
+) -> io::Error {
+    let fields = fields
+        .into_iter()
+        .map(|(k, v)| (k.into(), v.to_string()))
+        .collect::<Vec<_>>();
+    let mut message = context.to_string();
+    for (ix, (k, v)) in fields.iter().enumerate() {
+        match ix {
+            0 => message.push_str(" ("),
+            _ => message.push_str(", "),
+        }
+        message.push_str(k);
+        message.push('=');
+        message.push_str(v);
+    }
+    if !fields.is_empty() {
+        message.push(')')
+    }
+    context_fields(e, message, fields)
+}
+
+/// The structured fields of a single error in a chain, if it is (or wraps) a [`Context`].
+pub(crate) fn own_fields<'a>(
+    e: &'a (dyn Error + 'static),
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    let frame = match e.downcast_ref::<io::Error>() {
+        Some(e) => e.get_ref().and_then(|it| it.downcast_ref::<Context>()),
+        None => e.downcast_ref::<Context>(),
+    };
+    frame
+        .into_iter()
+        .flat_map(|it| it.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+}
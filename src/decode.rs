@@ -0,0 +1,170 @@
+use std::io::{self, ErrorKind::Interrupted};
+
+use encoding_rs::{Decoder, DecoderResult, Encoding};
+
+use crate::read::Hex;
+
+/// Bytes kept from the previous read of the inner reader, as a malformed
+/// sequence may start in an earlier buffer.
+const LOOKBEHIND: usize = 8;
+
+/// A reader which decodes text from a legacy encoding (or UTF-8 or UTF-16) to UTF-8
+/// as it is read.
+///
+/// A byte order mark, if any, overrides the encoding.
+/// Malformed sequences are [`InvalidData`](io::ErrorKind::InvalidData) errors with
+/// their byte offset in the inner stream, rather than being replaced.
+/// Any text decoded before a malformed sequence is returned first.
+///
+/// ```
+/// use std::io::{self, Read as _};
+/// use io_extra::DecodedReader;
+///
+/// let mut s = String::new();
+/// DecodedReader::new(&b"caf\xe9"[..], encoding_rs::WINDOWS_1252).read_to_string(&mut s)?;
+/// assert_eq!(s, "café");
+///
+/// let mut s = String::new();
+/// let e = DecodedReader::new(&b"\x82\xa0\xff\x82\xa2"[..], encoding_rs::SHIFT_JIS)
+///     .read_to_string(&mut s)
+///     .unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(e.to_string(), "malformed Shift_JIS at byte offset 2: ff");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub struct DecodedReader<R> {
+    inner: R,
+    decoder: Decoder,
+    /// Input from `inner`, starting with up to [`LOOKBEHIND`] bytes of the
+    /// previous read.
+    input: Vec<u8>,
+    /// How much of `input` has been decoded.
+    consumed: usize,
+    /// The offset of `input[0]` in `inner`.
+    offset: u64,
+    eof: bool,
+    /// Whether the decoder has been flushed at the end of `inner`.
+    finished: bool,
+    output: Vec<u8>,
+    /// How much of `output` has been returned.
+    returned: usize,
+    /// An error to return once `output` is drained.
+    error: Option<io::Error>,
+}
+
+impl<R> DecodedReader<R> {
+    /// Decode `inner` from `encoding`.
+    pub fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder(),
+            input: Vec::with_capacity(LOOKBEHIND + 8 * 1024),
+            consumed: 0,
+            offset: 0,
+            eof: false,
+            finished: false,
+            output: Vec::with_capacity(8 * 1024),
+            returned: 0,
+            error: None,
+        }
+    }
+    /// The encoding being decoded, which may differ from the one passed to
+    /// [`new`](Self::new) if there was a byte order mark.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.decoder.encoding()
+    }
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwrap the inner reader, discarding any buffered input.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: io::Read> DecodedReader<R> {
+    /// Read more input, keeping the tail of the previous input.
+    fn refill(&mut self) -> io::Result<()> {
+        let keep = self.input.len().min(LOOKBEHIND);
+        let drop = self.input.len() - keep;
+        self.input.drain(..drop);
+        self.offset += drop as u64;
+        self.consumed = keep;
+        let capacity = self.input.capacity();
+        self.input.resize(capacity, 0);
+        match self.inner.read(&mut self.input[keep..]) {
+            Ok(n) => {
+                self.input.truncate(keep + n);
+                self.eof = n == 0;
+                Ok(())
+            }
+            Err(e) => {
+                self.input.truncate(keep);
+                Err(e)
+            }
+        }
+    }
+    /// Decode some of `input` into `output`, which must be drained.
+    fn decode(&mut self) -> io::Result<()> {
+        while self.consumed == self.input.len() && !self.eof {
+            match self.refill() {
+                Ok(()) => {}
+                Err(e) if e.kind() == Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let (res, read, written) = self.decoder.decode_to_utf8_without_replacement(
+            &self.input[self.consumed..],
+            &mut self.output,
+            self.eof,
+        );
+        self.consumed += read;
+        self.output.truncate(written);
+        self.returned = 0;
+        match res {
+            DecoderResult::InputEmpty => self.finished = self.eof,
+            DecoderResult::OutputFull => {}
+            DecoderResult::Malformed(len, after) => {
+                let end = self.consumed.saturating_sub(usize::from(after));
+                let start = end.saturating_sub(usize::from(len));
+                self.error = Some(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "malformed {} at byte offset {}: {}",
+                        self.decoder.encoding().name(),
+                        self.offset + start as u64,
+                        Hex(&self.input[start..end])
+                    ),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for DecodedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.returned < self.output.len() {
+                let n = (self.output.len() - self.returned).min(buf.len());
+                buf[..n].copy_from_slice(&self.output[self.returned..self.returned + n]);
+                self.returned += n;
+                return Ok(n);
+            }
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            if self.finished {
+                return Ok(0);
+            }
+            let capacity = self.output.capacity();
+            self.output.resize(capacity, 0);
+            self.decode()?;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{cell::RefCell, fmt, io, mem};
+
+use crate::IoResultExt as _;
+
+thread_local! {
+    static DEFERRED: RefCell<Vec<io::Error>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Create a guard which runs `teardown` (an fsync, unlock, tempfile removal...)
+/// when it is [finished](DeferIo::finish) or dropped.
+///
+/// Finishing the guard with the result of the guarded work merges any teardown
+/// failure into it, as in [`IoResultExt::also_cleanup`](crate::IoResultExt::also_cleanup).
+/// If the guard is instead dropped (on an early return, or when unwinding),
+/// teardown failures are recorded for this thread, to be retrieved with
+/// [`take_deferred_errors`], rather than silently discarded.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{defer_io, take_deferred_errors, IoErrorExt as _};
+///
+/// fn work() -> io::Result<()> {
+///     let _guard = defer_io(|| Err(io::Error::other("couldn't release lock")));
+///     Err(io::Error::invalid_data("bad record"))?;
+///     Ok(())
+/// }
+///
+/// assert_eq!(work().unwrap_err().to_string(), "bad record");
+/// assert_eq!(take_deferred_errors()[0].to_string(), "couldn't release lock");
+///
+/// let guard = defer_io(|| Err(io::Error::other("couldn't release lock")));
+/// let e = guard.finish(Ok(())).unwrap_err();
+/// assert_eq!(e.to_string(), "couldn't release lock");
+/// assert!(take_deferred_errors().is_empty());
+/// ```
+pub fn defer_io<F: FnOnce() -> io::Result<()>>(teardown: F) -> DeferIo<F> {
+    DeferIo {
+        teardown: Some(teardown),
+    }
+}
+
+/// Take the teardown failures of [`DeferIo`] guards dropped on this thread.
+pub fn take_deferred_errors() -> Vec<io::Error> {
+    DEFERRED.with(|it| mem::take(&mut *it.borrow_mut()))
+}
+
+/// A guard which runs a teardown function, created by [`defer_io`].
+#[must_use = "the teardown runs immediately if the guard is not bound"]
+pub struct DeferIo<F: FnOnce() -> io::Result<()>> {
+    teardown: Option<F>,
+}
+
+impl<F: FnOnce() -> io::Result<()>> DeferIo<F> {
+    /// Run the teardown now, merging any failure into `result`.
+    pub fn finish<T>(mut self, result: io::Result<T>) -> io::Result<T> {
+        match self.teardown.take() {
+            Some(teardown) => result.also_cleanup(teardown),
+            None => result,
+        }
+    }
+    /// Disarm the guard, so that the teardown never runs.
+    pub fn cancel(mut self) {
+        self.teardown = None
+    }
+}
+
+impl<F: FnOnce() -> io::Result<()>> Drop for DeferIo<F> {
+    fn drop(&mut self) {
+        if let Some(teardown) = self.teardown.take() {
+            if let Err(e) = teardown() {
+                DEFERRED.with(|it| it.borrow_mut().push(e))
+            }
+        }
+    }
+}
+
+impl<F: FnOnce() -> io::Result<()>> fmt::Debug for DeferIo<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferIo")
+            .field("armed", &self.teardown.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,180 @@
+use std::{error::Error, fmt, io};
+
+use crate::context::{own_fields, Chain};
+
+/// The outermost human-readable message of an [`io::Error`], without any
+/// causes or OS error codes.
+///
+/// Created by [`IoErrorExt::headline`](crate::IoErrorExt::headline).
+#[derive(Debug, Clone, Copy)]
+pub struct Headline<'a>(pub(crate) &'a io::Error);
+
+impl fmt::Display for Headline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Always use the non-alternate form, which never includes causes.
+        let full = self.0.to_string();
+        match self.0.raw_os_error() {
+            Some(code) => full
+                .strip_suffix(&format!(" (os error {})", code))
+                .unwrap_or(&full)
+                .fmt(f),
+            None => full.fmt(f),
+        }
+    }
+}
+
+/// See [`IoErrorExt::summary`](crate::IoErrorExt::summary).
+pub(crate) fn summary(e: &io::Error, max_len: usize) -> String {
+    format!("{:?}: {}", e.kind(), Headline(e))
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || " -_.:/".contains(c) {
+                true => c,
+                false => '_',
+            },
+        )
+        .take(max_len)
+        .collect()
+}
+
+/// A single error in a chain, as seen by [`ContextDisplay`].
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    error: &'a (dyn Error + 'static),
+    depth: usize,
+}
+
+impl<'a> Frame<'a> {
+    /// How far down the chain this frame is, starting at zero for the outermost.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    /// The error at this frame.
+    pub fn error(&self) -> &'a (dyn Error + 'static) {
+        self.error
+    }
+    /// The structured fields attached at this frame.
+    pub fn fields(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        own_fields(self.error)
+    }
+    /// The value of the field named `key` attached at this frame.
+    pub fn field(&self, key: &str) -> Option<&'a str> {
+        self.fields().find_map(|(k, v)| (k == key).then_some(v))
+    }
+}
+
+/// The message of this frame alone, without its causes.
+impl fmt::Display for Frame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+type FrameFilter<'a> = dyn Fn(&Frame<'a>) -> bool + 'a;
+
+/// Renders a caller-chosen subset of the frames in an [`io::Error`]'s chain.
+///
+/// By default, every frame is shown, separated by `": "`, as with the alternate
+/// form of [`Display`](fmt::Display).
+///
+/// ```
+/// use std::io;
+/// use io_extra::{ctxt, ContextDisplay, IoErrorExt as _};
+///
+/// let path = "/etc/app.toml";
+/// let e = Err::<(), _>(io::Error::permission_denied("permission denied"))
+///     .map_err(ctxt!("opening {path}", path))
+///     .unwrap_err()
+///     .context("loading config")
+///     .context("starting up");
+///
+/// // A toast for the user.
+/// let toast = ContextDisplay::new(&e).filter(|frame| frame.depth() < 2);
+/// assert_eq!(toast.to_string(), "starting up: loading config");
+///
+/// // A line for the support bundle.
+/// let bundle = ContextDisplay::new(&e)
+///     .filter(|frame| frame.field("path").is_some())
+///     .with_fields(true);
+/// assert_eq!(bundle.to_string(), "opening /etc/app.toml (path=/etc/app.toml)");
+///
+/// // A log message.
+/// let log = ContextDisplay::new(&e).separator("\n  caused by: ");
+/// assert_eq!(
+///     log.to_string(),
+///     "starting up\n  caused by: loading config\n  caused by: opening /etc/app.toml\n  caused by: permission denied",
+/// );
+/// ```
+pub struct ContextDisplay<'a> {
+    error: &'a io::Error,
+    filter: Option<Box<FrameFilter<'a>>>,
+    separator: &'a str,
+    with_fields: bool,
+}
+
+impl<'a> ContextDisplay<'a> {
+    /// Render every frame of `error`.
+    pub fn new(error: &'a io::Error) -> Self {
+        Self {
+            error,
+            filter: None,
+            separator: ": ",
+            with_fields: false,
+        }
+    }
+    /// Only render frames for which `filter` returns `true`.
+    pub fn filter(mut self, filter: impl Fn(&Frame<'a>) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+    /// Render `separator` between frames.
+    pub fn separator(mut self, separator: &'a str) -> Self {
+        self.separator = separator;
+        self
+    }
+    /// Render each frame's structured fields after its message.
+    pub fn with_fields(mut self, with_fields: bool) -> Self {
+        self.with_fields = with_fields;
+        self
+    }
+    /// The frames which will be rendered, outermost first.
+    pub fn frames(&self) -> impl Iterator<Item = Frame<'a>> + '_ {
+        Chain::new(Some(self.error as &(dyn Error + 'static)))
+            .enumerate()
+            .map(|(depth, error)| Frame { error, depth })
+            .filter(|frame| self.filter.as_ref().is_none_or(|it| it(frame)))
+    }
+}
+
+impl fmt::Display for ContextDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ix, frame) in self.frames().enumerate() {
+            if ix != 0 {
+                f.write_str(self.separator)?
+            }
+            write!(f, "{}", frame)?;
+            if self.with_fields {
+                for (ix, (k, v)) in frame.fields().enumerate() {
+                    match ix {
+                        0 => write!(f, " ({}={}", k, v)?,
+                        _ => write!(f, ", {}={}", k, v)?,
+                    }
+                }
+                if frame.fields().next().is_some() {
+                    f.write_str(")")?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ContextDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextDisplay")
+            .field("error", &self.error)
+            .field("separator", &self.separator)
+            .field("with_fields", &self.with_fields)
+            .finish_non_exhaustive()
+    }
+}
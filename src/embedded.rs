@@ -0,0 +1,86 @@
+use std::io;
+
+use defmt::{write, Display2Format, Format, Formatter};
+
+use crate::{
+    context::{errors, Context},
+    journal::ErrorRecord,
+    kind, ComparableError, KnownError,
+};
+
+/// Renders an [`io::Error`] with [`defmt`], as its kind and chain of messages.
+///
+/// Messages attached by this crate are sent as they are, without going through
+/// `core::fmt`.
+/// Only foreign errors in the chain, like the innermost cause, are formatted.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{Defmt, IoErrorExt as _};
+///
+/// fn log(_: impl defmt::Format) {}
+///
+/// let e = io::Error::timed_out("no response").context("polling sensor");
+/// log(Defmt(&e)); // TimedOut: polling sensor: no response
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Defmt<'a>(pub &'a io::Error);
+
+impl Format for Defmt<'_> {
+    fn format(&self, f: Formatter<'_>) {
+        write!(f, "{=str}", kind::name(self.0.kind()));
+        if self.0.get_ref().is_none() && self.0.raw_os_error().is_some() {
+            write!(f, ": {}", Display2Format(self.0))
+        }
+        for e in errors(self.0) {
+            match (e.downcast_ref::<Context>(), e.downcast_ref::<io::Error>()) {
+                (Some(frame), _) => {
+                    if let Some(message) = frame.message() {
+                        write!(f, ": {=str}", message)
+                    }
+                }
+                // Its payload is next.
+                (None, Some(e)) if e.get_ref().is_some() => {}
+                _ => write!(f, ": {}", Display2Format(e)),
+            }
+        }
+    }
+}
+
+impl Format for KnownError {
+    fn format(&self, f: Formatter<'_>) {
+        write!(f, "{=str}", kind::name(self.kind()));
+        match self {
+            Self::NotFound { path: Some(path) }
+            | Self::PermissionDenied { path: Some(path) }
+            | Self::AlreadyExists { path: Some(path) } => match path.to_str() {
+                Some(path) => write!(f, ": {=str}", path),
+                None => write!(f, ": {}", Display2Format(&path.display())),
+            },
+            Self::Timeout {
+                elapsed: Some(elapsed),
+            } => write!(f, " after {=u128}ms", elapsed.as_millis()),
+            _ => {}
+        }
+    }
+}
+
+impl Format for ComparableError {
+    fn format(&self, f: Formatter<'_>) {
+        write!(f, "{=str}: {=str}", kind::name(self.kind()), self.message())
+    }
+}
+
+impl Format for ErrorRecord {
+    fn format(&self, f: Formatter<'_>) {
+        write!(
+            f,
+            "{=str}: {=str}",
+            kind::name(self.kind),
+            self.message.as_str()
+        );
+        for cause in &self.causes {
+            write!(f, ": {=str}", cause.as_str())
+        }
+    }
+}
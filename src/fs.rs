@@ -0,0 +1,491 @@
+//! Wrappers around [`std::fs`] which attach the operation and the path(s) to
+//! any error.
+//!
+//! The path is also [recorded](crate::path()), for callers to recover.
+//!
+//! ```
+//! use std::{io, path::Path};
+//! use io_extra::{fs, path};
+//!
+//! let e = fs::read_to_string("/no/such/config.toml").unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::NotFound);
+//! assert!(format!("{:#}", e).starts_with("reading /no/such/config.toml: "));
+//! assert_eq!(path(&e), Some(Path::new("/no/such/config.toml")));
+//! ```
+
+use std::{
+    fmt,
+    fs::{self, File, Metadata, ReadDir},
+    io::Write as _,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::context::path_context;
+
+fn annotate<T>(res: io::Result<T>, operation: &str, path: &Path) -> io::Result<T> {
+    res.map_err(|e| {
+        path_context(e, path.to_path_buf(), |path| {
+            format!("{} {}", operation, path.display())
+        })
+    })
+}
+
+fn annotate2<T>(res: io::Result<T>, operation: &str, from: &Path, to: &Path) -> io::Result<T> {
+    res.map_err(|e| {
+        path_context(e, from.to_path_buf(), |from| {
+            format!("{} {} to {}", operation, from.display(), to.display())
+        })
+    })
+}
+
+/// [`std::fs::read`], annotated with "reading `path`".
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    annotate(fs::read(path), "reading", path)
+}
+
+/// [`std::fs::read_to_string`], annotated with "reading `path`".
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    annotate(fs::read_to_string(path), "reading", path)
+}
+
+/// [`std::fs::write`], annotated with "writing `path`".
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::write(path, contents), "writing", path)
+}
+
+/// [`File::open`], annotated with "opening `path`".
+pub fn open(path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+    annotate(File::open(path), "opening", path)
+}
+
+/// [`File::create`], annotated with "creating `path`".
+pub fn create(path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+    annotate(File::create(path), "creating", path)
+}
+
+/// [`std::fs::create_dir`], annotated with "creating directory `path`".
+pub fn create_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::create_dir(path), "creating directory", path)
+}
+
+/// [`std::fs::create_dir_all`], annotated with "creating directory `path`".
+pub fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::create_dir_all(path), "creating directory", path)
+}
+
+/// [`std::fs::remove_file`], annotated with "removing `path`".
+pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::remove_file(path), "removing", path)
+}
+
+/// [`std::fs::remove_dir`], annotated with "removing directory `path`".
+pub fn remove_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::remove_dir(path), "removing directory", path)
+}
+
+/// [`std::fs::remove_dir_all`], annotated with "removing directory `path`".
+pub fn remove_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    annotate(fs::remove_dir_all(path), "removing directory", path)
+}
+
+/// [`std::fs::metadata`], annotated with "inspecting `path`".
+pub fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    let path = path.as_ref();
+    annotate(fs::metadata(path), "inspecting", path)
+}
+
+/// [`std::fs::symlink_metadata`], annotated with "inspecting `path`".
+pub fn symlink_metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    let path = path.as_ref();
+    annotate(fs::symlink_metadata(path), "inspecting", path)
+}
+
+/// [`std::fs::read_dir`], annotated with "listing `path`".
+///
+/// Errors from the returned iterator are not annotated.
+pub fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+    let path = path.as_ref();
+    annotate(fs::read_dir(path), "listing", path)
+}
+
+/// [`std::fs::read_link`], annotated with "reading link `path`".
+pub fn read_link(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    annotate(fs::read_link(path), "reading link", path)
+}
+
+/// [`std::fs::canonicalize`], annotated with "resolving `path`".
+pub fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    annotate(fs::canonicalize(path), "resolving", path)
+}
+
+/// [`std::fs::copy`], annotated with "copying `from` to `to`".
+///
+/// `from` is the [recorded](crate::path()) path.
+pub fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<u64> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    annotate2(fs::copy(from, to), "copying", from, to)
+}
+
+/// [`std::fs::rename`], annotated with "renaming `from` to `to`".
+///
+/// `from` is the [recorded](crate::path()) path.
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    annotate2(fs::rename(from, to), "renaming", from, to)
+}
+
+/// [`std::fs::hard_link`], annotated with "linking `original` to `link`".
+///
+/// `original` is the [recorded](crate::path()) path.
+pub fn hard_link(original: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    let (original, link) = (original.as_ref(), link.as_ref());
+    annotate2(fs::hard_link(original, link), "linking", original, link)
+}
+
+/// The first component of a path which cannot be reached, as found by
+/// [`diagnose_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathDiagnosis {
+    /// Every component exists, and the path itself can be inspected.
+    Reachable,
+    /// The component does not exist, though its parent does.
+    Missing(PathBuf),
+    /// The component is not a directory, but has components beneath it.
+    NotADirectory(PathBuf),
+    /// The component is a directory which may not be traversed.
+    PermissionDenied(PathBuf),
+}
+
+impl fmt::Display for PathDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reachable => f.write_str("every component is reachable"),
+            Self::Missing(it) => write!(f, "{} does not exist", it.display()),
+            Self::NotADirectory(it) => write!(f, "{} is not a directory", it.display()),
+            Self::PermissionDenied(it) => write!(f, "{} may not be traversed", it.display()),
+        }
+    }
+}
+
+/// Inspect each ancestor of `path`, outermost first, to find the component
+/// which makes it unreachable.
+///
+/// ```
+/// use std::path::PathBuf;
+/// use io_extra::fs::{diagnose_path, PathDiagnosis};
+///
+/// assert_eq!(diagnose_path("/")?, PathDiagnosis::Reachable);
+/// assert_eq!(
+///     diagnose_path("/no/such/config.toml")?,
+///     PathDiagnosis::Missing(PathBuf::from("/no")),
+/// );
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn diagnose_path(path: impl AsRef<Path>) -> io::Result<PathDiagnosis> {
+    let path = path.as_ref();
+    let mut ancestors = path
+        .ancestors()
+        .filter(|it| !it.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    ancestors.reverse();
+    let mut parent = None::<&Path>;
+    for ancestor in ancestors {
+        let denied = || PathDiagnosis::PermissionDenied(parent.unwrap_or(ancestor).to_path_buf());
+        match fs::metadata(ancestor) {
+            Ok(it) if ancestor != path && !it.is_dir() => {
+                return Ok(PathDiagnosis::NotADirectory(ancestor.to_path_buf()))
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(PathDiagnosis::Missing(ancestor.to_path_buf()))
+            }
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => return Ok(denied()),
+            Err(e) => return annotate(Err(e), "inspecting", ancestor),
+        }
+        parent = Some(ancestor);
+    }
+    Ok(PathDiagnosis::Reachable)
+}
+
+/// If `e` is a failure to find or reach `path`, attach a [diagnosis](diagnose_path)
+/// of the component at fault.
+///
+/// Other errors, and paths which turn out to be reachable, are returned
+/// unchanged.
+///
+/// ```
+/// use std::fs;
+/// use io_extra::fs::diagnose;
+///
+/// let path = "/no/such/config.toml";
+/// let e = diagnose(fs::read(path).unwrap_err(), path);
+/// assert_eq!(
+///     format!("{:#}", e),
+///     "/no does not exist: No such file or directory (os error 2)",
+/// );
+/// ```
+pub fn diagnose(e: io::Error, path: impl AsRef<Path>) -> io::Error {
+    let relevant = match e.kind() {
+        ErrorKind::NotFound | ErrorKind::PermissionDenied => true,
+        #[cfg(io_error_more)]
+        ErrorKind::NotADirectory => true,
+        _ => false,
+    };
+    if !relevant {
+        return e;
+    }
+    match diagnose_path(path) {
+        Ok(PathDiagnosis::Reachable) | Err(_) => e,
+        Ok(diagnosis) => crate::context(e, diagnosis),
+    }
+}
+
+/// A directory which is removed, with its contents, when dropped.
+///
+/// Created by [`tempdir_in`].
+#[derive(Debug)]
+pub struct TempDir {
+    /// [`None`] once kept or closed.
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    /// The path of the directory.
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().expect("only unset when consumed")
+    }
+    /// Don't remove the directory, returning its path.
+    pub fn keep(mut self) -> PathBuf {
+        self.path.take().expect("only unset when consumed")
+    }
+    /// Remove the directory, returning any error, which would be ignored on drop.
+    pub fn close(mut self) -> io::Result<()> {
+        remove_dir_all(self.path.take().expect("only unset when consumed"))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = fs::remove_dir_all(path);
+        }
+    }
+}
+
+/// Create a new, uniquely named directory in `dir`, annotated with "creating
+/// temporary directory in `dir`".
+///
+/// ```
+/// use io_extra::fs::tempdir_in;
+///
+/// let dir = tempdir_in(std::env::temp_dir())?;
+/// let path = dir.path().to_path_buf();
+/// assert!(path.is_dir());
+/// drop(dir);
+/// assert!(!path.exists());
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn tempdir_in(dir: impl AsRef<Path>) -> io::Result<TempDir> {
+    let dir = dir.as_ref();
+    loop {
+        let path = dir.join(unique_name(".tmp"));
+        match fs::create_dir(&path) {
+            Ok(()) => return Ok(TempDir { path: Some(path) }),
+            // Left behind by an earlier process with our PID.
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return annotate(Err(e), "creating temporary directory in", dir),
+        }
+    }
+}
+
+/// A name which no other call in any running process will return.
+fn unique_name(prefix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}-{}",
+        prefix,
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Create a temporary directory in `dir`, as in [`tempdir_in`], after checking
+/// that its filesystem has at least `required_bytes` available.
+///
+/// If not, this fails early with [`ErrorKind::StorageFull`], rather than
+/// part way through filling the directory.
+///
+/// ```
+/// use std::{env, io};
+/// use io_extra::fs::tempdir_in_checked;
+///
+/// let dir = tempdir_in_checked(env::temp_dir(), 1)?;
+///
+/// let e = tempdir_in_checked(env::temp_dir(), u64::MAX).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::StorageFull);
+/// assert!(e.to_string().starts_with("need 16 EiB, only "));
+/// # Ok::<_, io::Error>(())
+/// ```
+#[cfg(feature = "fs4")]
+pub fn tempdir_in_checked(dir: impl AsRef<Path>, required_bytes: u64) -> io::Result<TempDir> {
+    let dir = dir.as_ref();
+    let available = free_space(dir)?.available;
+    if available < required_bytes {
+        #[cfg(io_error_more)]
+        let kind = ErrorKind::StorageFull;
+        #[cfg(not(io_error_more))]
+        let kind = ErrorKind::Other;
+        return Err(io::Error::new(
+            kind,
+            format!(
+                "need {}, only {} free on {}",
+                Bytes(required_bytes),
+                Bytes(available),
+                dir.display()
+            ),
+        ));
+    }
+    tempdir_in(dir)
+}
+
+/// The space on a filesystem, as found by [`free_space`].
+#[cfg(feature = "fs4")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SpaceInfo {
+    /// The bytes available to unprivileged users.
+    pub available: u64,
+    /// The free bytes, including any reserved for privileged users.
+    pub free: u64,
+    /// The size of the filesystem, in bytes.
+    pub total: u64,
+}
+
+/// Rendered like "512 MiB available of 2 GiB".
+#[cfg(feature = "fs4")]
+impl fmt::Display for SpaceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} available of {}",
+            Bytes(self.available),
+            Bytes(self.total)
+        )
+    }
+}
+
+/// The space on the filesystem containing `path`, annotated with "checking
+/// free space on `path`".
+///
+/// ```
+/// use std::io;
+/// use io_extra::fs::free_space;
+///
+/// let space = free_space(std::env::temp_dir())?;
+/// assert!(space.available <= space.free && space.free <= space.total);
+///
+/// let e = free_space("/no/such/dir").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert!(format!("{:#}", e).starts_with("checking free space on /no/such/dir: "));
+/// # Ok::<_, io::Error>(())
+/// ```
+#[cfg(feature = "fs4")]
+pub fn free_space(path: impl AsRef<Path>) -> io::Result<SpaceInfo> {
+    let path = path.as_ref();
+    let stats = annotate(fs4::statvfs(path), "checking free space on", path)?;
+    Ok(SpaceInfo {
+        available: stats.available_space(),
+        free: stats.free_space(),
+        total: stats.total_space(),
+    })
+}
+
+/// A size in bytes, rendered with binary units, like "1.5 GiB".
+#[cfg(feature = "fs4")]
+struct Bytes(u64);
+
+#[cfg(feature = "fs4")]
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        match value.fract() == 0.0 {
+            true => write!(f, "{} {}", value, UNITS[unit]),
+            false => write!(f, "{:.1} {}", value, UNITS[unit]),
+        }
+    }
+}
+
+/// Check that files can be created in `dir`, by creating, writing, and
+/// removing a probe file.
+///
+/// Failures say why `dir` isn't writable, distinguishing read-only
+/// filesystems, missing permissions, and full disks or exhausted quotas, and
+/// [record](crate::path()) `dir` as the path.
+///
+/// ```
+/// use std::io;
+/// use io_extra::fs::check_writable;
+///
+/// check_writable(std::env::temp_dir())?;
+///
+/// let e = check_writable("/no/such/dir").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert!(format!("{:#}", e).starts_with("creating a probe file in /no/such/dir: "));
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn check_writable(dir: impl AsRef<Path>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    let probe = dir.join(unique_name(".probe"));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|e| not_writable(e, dir, "creating"))?;
+    let written = file
+        .write_all(&[0; 4096])
+        .and_then(|()| file.sync_all())
+        .map_err(|e| not_writable(e, dir, "writing"));
+    drop(file);
+    let removed = fs::remove_file(&probe).map_err(|e| not_writable(e, dir, "removing"));
+    written.and(removed)
+}
+
+/// Annotate a failure at `step` of [`check_writable`].
+fn not_writable(e: io::Error, dir: &Path, step: &str) -> io::Error {
+    let reason = match e.kind() {
+        ErrorKind::PermissionDenied => Some("is not writable by this user"),
+        #[cfg(io_error_more)]
+        ErrorKind::ReadOnlyFilesystem => Some("is on a read-only filesystem"),
+        #[cfg(io_error_more)]
+        ErrorKind::StorageFull => Some("is on a full filesystem"),
+        #[cfg(io_error_quota_exceeded)]
+        ErrorKind::QuotaExceeded => Some("is over its disk quota"),
+        _ => None,
+    };
+    path_context(e, dir.to_path_buf(), |dir| match reason {
+        Some(reason) => format!("{} {}", dir.display(), reason),
+        None => format!("{} a probe file in {}", step, dir.display()),
+    })
+}
@@ -0,0 +1,81 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::RwLock,
+};
+
+static DEFAULTS: RwLock<Vec<(ErrorKind, &'static str)>> = RwLock::new(Vec::new());
+
+/// Set the default help URL for errors of `kind`, process-wide, replacing any
+/// previous default.
+///
+/// It is used by [`help_of`] when no URL was attached with
+/// [`with_help`](crate::IoErrorExt::with_help).
+pub fn register_help(kind: ErrorKind, url: &'static str) {
+    let mut defaults = DEFAULTS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    defaults.retain(|(it, _)| *it != kind);
+    defaults.push((kind, url))
+}
+
+/// The help URL for `e`: the outermost one attached with
+/// [`with_help`](crate::IoErrorExt::with_help), or the [registered](register_help)
+/// default for its kind.
+///
+/// This is surfaced as a `help:` line by [`Report`](crate::Report).
+///
+/// ```
+/// use std::io;
+/// use io_extra::{help_of, register_help, IoErrorExt as _, Report};
+///
+/// register_help(io::ErrorKind::PermissionDenied, "https://docs.example.com/errors/permissions");
+///
+/// let e = io::Error::permission_denied("can't read key").context("signing release");
+/// assert_eq!(help_of(&e), Some("https://docs.example.com/errors/permissions"));
+///
+/// let e = e.with_help("https://docs.example.com/errors/E1042");
+/// assert_eq!(
+///     Report::new(e).to_string(),
+///     "signing release: can't read key\nhelp: https://docs.example.com/errors/E1042",
+/// );
+/// ```
+pub fn help_of(e: &io::Error) -> Option<&str> {
+    crate::context::frames(e)
+        .find_map(|frame| frame.help.as_deref())
+        .or_else(|| {
+            DEFAULTS
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .iter()
+                .find_map(|(kind, url)| (*kind == e.kind()).then_some(*url))
+        })
+}
+
+/// The suggestions attached to `e` with [`suggest`](crate::IoErrorExt::suggest),
+/// outermost first.
+///
+/// These are surfaced as `hint:` lines by [`Report`](crate::Report), separately
+/// from the chain of causes.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{hints, IoErrorExt as _, Report};
+///
+/// let e = io::Error::permission_denied("token expired")
+///     .suggest("run `app login` to refresh credentials")
+///     .context("publishing package");
+/// assert_eq!(hints(&e).collect::<Vec<_>>(), ["run `app login` to refresh credentials"]);
+/// assert_eq!(
+///     Report::new(e).pretty(true).to_string(),
+///     "\
+/// publishing package
+///
+/// Caused by:
+///       token expired
+///
+/// hint: run `app login` to refresh credentials"
+/// );
+/// ```
+pub fn hints(e: &io::Error) -> impl Iterator<Item = &str> {
+    crate::context::frames(e).flat_map(|frame| frame.hints.iter().map(String::as_str))
+}
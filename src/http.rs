@@ -0,0 +1,139 @@
+//! Rendering [`io::Error`]s as HTTP responses, for services which expose
+//! filesystem or socket operations over REST.
+
+use std::io::{self, ErrorKind::*};
+
+use serde_json::{Map, Value};
+
+use crate::IoErrorExt as _;
+
+/// The media type of [`Problem::to_json`].
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// A suitable HTTP status code for errors of `kind`.
+///
+/// ```
+/// use std::io;
+/// use io_extra::http::status;
+///
+/// assert_eq!(status(io::ErrorKind::NotFound), 404);
+/// assert_eq!(status(io::ErrorKind::Other), 500);
+/// ```
+pub fn status(kind: io::ErrorKind) -> u16 {
+    match kind {
+        InvalidInput => 400,
+        PermissionDenied => 403,
+        NotFound => 404,
+        AlreadyExists => 409,
+        InvalidData => 422,
+        Unsupported => 501,
+        ConnectionRefused | ConnectionReset | ConnectionAborted | NotConnected | BrokenPipe => 502,
+        WouldBlock | Interrupted => 503,
+        TimedOut => 504,
+        OutOfMemory => 507,
+        _ => 500,
+    }
+}
+
+/// An RFC 7807 problem details object describing an [`io::Error`].
+///
+/// - `type` is derived from the [kind](io::Error::kind), under a configurable
+///   [prefix](Self::type_prefix).
+/// - `title` is the [headline](crate::IoErrorExt::headline).
+/// - `detail` is the full chain of causes.
+/// - Each structured [field](crate::fields()) becomes an extension member.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{ctxt, http::Problem, IoErrorExt as _};
+///
+/// let path = "/srv/data/report.csv";
+/// let e = Err::<(), _>(io::Error::not_found("no such file"))
+///     .map_err(ctxt!("opening {path}", path))
+///     .unwrap_err();
+///
+/// let problem = Problem::new(&e).type_prefix("https://example.com/problems/");
+/// assert_eq!(problem.status(), 404);
+/// assert_eq!(
+///     problem.to_json(),
+///     r#"{"detail":"opening /srv/data/report.csv: no such file","path":"/srv/data/report.csv","status":404,"title":"opening /srv/data/report.csv","type":"https://example.com/problems/NotFound"}"#,
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Problem<'a> {
+    error: &'a io::Error,
+    type_prefix: &'a str,
+}
+
+impl<'a> Problem<'a> {
+    /// Describe `error`.
+    pub fn new(error: &'a io::Error) -> Self {
+        Self {
+            error,
+            type_prefix: "urn:io-error:",
+        }
+    }
+    /// Prefix the `type` member with `prefix`, instead of `urn:io-error:`.
+    pub fn type_prefix(mut self, prefix: &'a str) -> Self {
+        self.type_prefix = prefix;
+        self
+    }
+    /// The HTTP status code, as in [`status`].
+    pub fn status(&self) -> u16 {
+        status(self.error.kind())
+    }
+    /// Render the response body.
+    ///
+    /// The `detail` member includes the causes of any error, not just this
+    /// crate's context.
+    ///
+    /// ```
+    /// use std::{error::Error, fmt, io};
+    /// use io_extra::http::Problem;
+    ///
+    /// #[derive(Debug)]
+    /// struct Upload(io::Error);
+    /// impl fmt::Display for Upload {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         f.write_str("uploading backup")
+    ///     }
+    /// }
+    /// impl Error for Upload {
+    ///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+    ///         Some(&self.0)
+    ///     }
+    /// }
+    ///
+    /// let e = io::Error::other(Upload(io::Error::other("disk quota exceeded")));
+    /// assert!(Problem::new(&e)
+    ///     .to_json()
+    ///     .starts_with(r#"{"detail":"uploading backup: disk quota exceeded","#));
+    /// ```
+    ///
+    /// Fields which would clobber a standard member are omitted, as are repeated
+    /// fields, with the outermost winning.
+    pub fn to_json(&self) -> String {
+        let mut members = Map::new();
+        members.insert(
+            String::from("type"),
+            Value::from(format!("{}{:?}", self.type_prefix, self.error.kind())),
+        );
+        members.insert(
+            String::from("title"),
+            Value::from(self.error.headline().to_string()),
+        );
+        members.insert(String::from("status"), Value::from(self.status()));
+        // Not `{:#}`, which only shows the causes of this crate's context.
+        let detail = crate::chain(self.error)
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>()
+            .join(": ");
+        members.insert(String::from("detail"), Value::from(detail));
+        for (key, value) in crate::fields(self.error) {
+            if key != "instance" && !members.contains_key(key) {
+                members.insert(String::from(key), Value::from(value));
+            }
+        }
+        Value::Object(members).to_string()
+    }
+}
@@ -0,0 +1,81 @@
+//! Classifying the errors returned by S3-style object storage services, for
+//! crates implementing [`Read`](std::io::Read) and [`Write`](std::io::Write)
+//! over them.
+//!
+//! ```
+//! use std::io;
+//! use io_extra::http_object_store;
+//!
+//! let e = http_object_store::error(404, "NoSuchKey", "The specified key does not exist.");
+//! assert_eq!(e.kind(), io::ErrorKind::NotFound);
+//! assert_eq!(format!("{:#}", e), "HTTP 404 NoSuchKey: The specified key does not exist.");
+//!
+//! // Throttling is transient.
+//! assert_eq!(http_object_store::kind(503, "SlowDown"), io::ErrorKind::WouldBlock);
+//! ```
+
+use std::{
+    fmt,
+    io::{self, ErrorKind::*},
+};
+
+use crate::context::context_fields;
+
+/// The [`ErrorKind`](io::ErrorKind) for an HTTP `status` and service error `code`.
+///
+/// Well-known codes from Amazon S3, Google Cloud Storage, and Azure Blob Storage
+/// are recognised, falling back to the status.
+pub fn kind(status: u16, code: &str) -> io::ErrorKind {
+    match code {
+        "NoSuchKey" | "NoSuchBucket" | "NoSuchUpload" | "NoSuchVersion" | "BlobNotFound"
+        | "ContainerNotFound" | "ResourceNotFound" | "notFound" => NotFound,
+        "AccessDenied"
+        | "AllAccessDisabled"
+        | "AccountProblem"
+        | "InvalidAccessKeyId"
+        | "SignatureDoesNotMatch"
+        | "ExpiredToken"
+        | "AuthenticationFailed"
+        | "AuthorizationFailure"
+        | "forbidden" => PermissionDenied,
+        "BucketAlreadyExists"
+        | "BucketAlreadyOwnedByYou"
+        | "BlobAlreadyExists"
+        | "ContainerAlreadyExists" => AlreadyExists,
+        "SlowDown"
+        | "ServerBusy"
+        | "Throttling"
+        | "ThrottlingException"
+        | "RequestLimitExceeded"
+        | "TooManyRequests"
+        | "rateLimitExceeded" => WouldBlock,
+        "RequestTimeout" | "OperationTimedOut" => TimedOut,
+        "InvalidArgument" | "InvalidRequest" | "InvalidBucketName" | "KeyTooLongError"
+        | "MalformedXML" | "InvalidRange" | "invalid" => InvalidInput,
+        "NotImplemented" => Unsupported,
+        _ => match status {
+            400 | 416 => InvalidInput,
+            401 | 403 => PermissionDenied,
+            404 => NotFound,
+            408 | 504 => TimedOut,
+            409 => AlreadyExists,
+            429 | 503 => WouldBlock,
+            501 => Unsupported,
+            _ => Other,
+        },
+    }
+}
+
+/// Create an error for an HTTP `status` and service error `code`, as in [`kind`].
+///
+/// The status and code are recorded as structured [fields](crate::fields()).
+pub fn error(status: u16, code: &str, message: impl fmt::Display) -> io::Error {
+    context_fields(
+        io::Error::new(kind(status, code), message.to_string()),
+        format_args!("HTTP {} {}", status, code),
+        [
+            ("status", &status as &dyn fmt::Display),
+            ("code", &code as _),
+        ],
+    )
+}
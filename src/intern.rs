@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+static CAPACITY: AtomicUsize = AtomicUsize::new(0);
+static STRINGS: Mutex<BTreeSet<Arc<str>>> = Mutex::new(BTreeSet::new());
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Share the allocations of repeated context messages, process-wide.
+///
+/// Hot messages like `"reading chunk"` or `"flushing wal"` are then stored once,
+/// with each frame holding a reference-counted pointer, rather than its own copy.
+/// Up to `capacity` distinct messages are kept: once full, new messages are
+/// stored per frame, as usual, so that unique messages (containing paths, or
+/// IDs) don't grow the table without bound.
+///
+/// `None` (the default) disables interning.
+/// See [`interning_stats`] for measuring the hit rate.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{context, interning_stats, set_context_interning};
+///
+/// set_context_interning(Some(1024));
+/// for _ in 0..4 {
+///     let _ = context(io::Error::other("disk on fire"), "flushing wal");
+/// }
+/// let stats = interning_stats();
+/// assert_eq!((stats.hits, stats.misses, stats.entries), (3, 1, 1));
+/// assert_eq!(stats.hit_rate(), 0.75);
+/// ```
+pub fn set_context_interning(capacity: Option<usize>) {
+    CAPACITY.store(capacity.unwrap_or(0), Relaxed);
+    if capacity.is_none() {
+        strings().clear()
+    }
+}
+
+/// Counters for [`set_context_interning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct InternStats {
+    /// Messages which were already interned.
+    pub hits: u64,
+    /// Messages which were newly interned, or didn't fit.
+    pub misses: u64,
+    /// The number of distinct messages currently interned.
+    pub entries: usize,
+}
+
+impl InternStats {
+    /// The proportion of messages which were already interned, or `0.0` if
+    /// none have been seen.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+/// The current [`InternStats`].
+pub fn interning_stats() -> InternStats {
+    InternStats {
+        hits: HITS.load(Relaxed),
+        misses: MISSES.load(Relaxed),
+        entries: strings().len(),
+    }
+}
+
+/// A shared copy of `s`, if interning is enabled and there is room.
+pub(crate) fn intern(s: &str) -> Option<Arc<str>> {
+    let capacity = CAPACITY.load(Relaxed);
+    if capacity == 0 {
+        return None;
+    }
+    let mut strings = strings();
+    if let Some(it) = strings.get(s) {
+        HITS.fetch_add(1, Relaxed);
+        return Some(it.clone());
+    }
+    MISSES.fetch_add(1, Relaxed);
+    match strings.len() < capacity {
+        true => {
+            let it = Arc::<str>::from(s);
+            strings.insert(it.clone());
+            Some(it)
+        }
+        false => None,
+    }
+}
+
+fn strings() -> MutexGuard<'static, BTreeSet<Arc<str>>> {
+    STRINGS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,419 @@
+//! A size-capped, rotating, on-disk record of errors, for offline diagnosis.
+//!
+//! The journal is a text file with one record per line.
+//! Each record is written with a single append, and synced to disk, so a crash
+//! can at worst leave a truncated final line, which [`Reader`] skips.
+//!
+//! ```
+//! use std::io;
+//! use io_extra::{ctxt, fields, journal, IoErrorExt as _};
+//!
+//! let dir = std::env::temp_dir().join(format!("io-extra-journal-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir)?;
+//! let path = dir.join("errors.journal");
+//!
+//! let mut writer = journal::Writer::open(&path, 1024 * 1024)?;
+//! writer.append(&io::Error::not_found("no such table").context("loading schema"))?;
+//!
+//! let records = journal::read(&path).collect::<io::Result<Vec<_>>>()?;
+//! assert_eq!(records[0].kind, io::ErrorKind::NotFound);
+//! assert_eq!(records[0].message, "loading schema");
+//! assert_eq!(records[0].causes, ["no such table"]);
+//!
+//! // Records can be turned back into errors, for use with the rest of this crate.
+//! let e = io::Error::from(records[0].clone());
+//! assert_eq!(format!("{:#}", e), "loading schema: no such table");
+//!
+//! // Structured fields are kept, even if they contain tabs or newlines.
+//! let table = "users\tv2";
+//! let e = Err::<(), _>(io::Error::not_found("no such table"))
+//!     .map_err(ctxt!("loading {table}", table))
+//!     .unwrap_err();
+//! writer.append(&e)?;
+//! let record = journal::read(&path).last().unwrap()?;
+//! assert_eq!(record.fields, [("table".to_owned(), table.to_owned())]);
+//! assert!(fields(&io::Error::from(record)).eq([("table", table)]));
+//! # std::fs::remove_dir_all(&dir)?;
+//! # Ok::<_, io::Error>(())
+//! ```
+
+use std::{
+    error::Error as _,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, ErrorKind, Write as _},
+    iter,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    context::{context, Chain},
+    IoErrorExt as _,
+};
+
+const VERSION: &str = "v2";
+/// Records without fields, which are still read.
+const VERSION_1: &str = "v1";
+
+/// An error, as recorded in the journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ErrorRecord {
+    /// When the error was recorded.
+    pub timestamp: SystemTime,
+    /// The kind of the error.
+    ///
+    /// Kinds this crate doesn't know by name are read back as [`ErrorKind::Other`].
+    pub kind: ErrorKind,
+    /// The outermost message.
+    pub message: String,
+    /// The messages of each cause, outermost first.
+    pub causes: Vec<String>,
+    /// The structured [fields](crate::fields()) of the error and its causes.
+    pub fields: Vec<(String, String)>,
+}
+
+impl ErrorRecord {
+    /// Snapshot `e`, timestamped now.
+    pub fn new(e: &io::Error) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            kind: e.kind(),
+            message: e.to_string(),
+            causes: Chain::new(e.source()).map(|it| it.to_string()).collect(),
+            fields: crate::fields(e)
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        let millis = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut line = format!(
+            "{}\t{}\t{:?}\t{}",
+            VERSION,
+            millis,
+            self.kind,
+            self.fields.len()
+        );
+        for (key, value) in &self.fields {
+            line.push('\t');
+            line.push_str(&escape(key));
+            line.push('\t');
+            line.push_str(&escape(value));
+        }
+        line.push('\t');
+        line.push_str(&escape(&self.message));
+        for cause in &self.causes {
+            line.push('\t');
+            line.push_str(&escape(cause));
+        }
+        line.push('\n');
+        line
+    }
+
+    fn from_line(line: &str) -> io::Result<Self> {
+        let malformed = || io::Error::invalid_data("malformed journal record");
+        let mut columns = line.split('\t');
+        let (Some(version), Some(millis), Some(kind)) =
+            (columns.next(), columns.next(), columns.next())
+        else {
+            return Err(malformed());
+        };
+        let fields = match version {
+            VERSION => {
+                let count = columns
+                    .next()
+                    .and_then(|it| it.parse::<usize>().ok())
+                    .ok_or_else(malformed)?;
+                let mut fields = Vec::new();
+                for _ in 0..count {
+                    match (columns.next(), columns.next()) {
+                        (Some(key), Some(value)) => fields.push((unescape(key), unescape(value))),
+                        _ => return Err(malformed()),
+                    }
+                }
+                fields
+            }
+            VERSION_1 => Vec::new(),
+            version => {
+                return Err(io::Error::unsupported(format!(
+                    "unsupported journal version {}",
+                    version
+                )))
+            }
+        };
+        let message = columns.next().ok_or_else(malformed)?;
+        Ok(Self {
+            timestamp: UNIX_EPOCH
+                + Duration::from_millis(millis.parse().map_err(|e| {
+                    io::Error::invalid_data(e).context("invalid journal timestamp")
+                })?),
+            kind: crate::kind::from_name(kind).unwrap_or(ErrorKind::Other),
+            message: unescape(message),
+            causes: columns.map(unescape).collect(),
+            fields,
+        })
+    }
+}
+
+impl From<&io::Error> for ErrorRecord {
+    fn from(e: &io::Error) -> Self {
+        Self::new(e)
+    }
+}
+
+/// The outermost message, followed by the causes in the alternate form, as with
+/// [`io::Error`]s from this crate.
+impl fmt::Display for ErrorRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if f.alternate() {
+            for cause in &self.causes {
+                write!(f, ": {}", cause)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild an error with the same kind and messages, and with its fields on the
+/// outermost frame.
+impl From<ErrorRecord> for io::Error {
+    fn from(value: ErrorRecord) -> Self {
+        let mut messages = iter::once(value.message).chain(value.causes).rev();
+        let innermost = match messages.next() {
+            Some(message) => io::Error::new(value.kind, message),
+            None => io::Error::from(value.kind),
+        };
+        crate::context::with_fields(messages.fold(innermost, context), value.fields)
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('t') => out.push('\t'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends [`ErrorRecord`]s to a journal file.
+///
+/// When appending a record would take the file past its size cap, the file is
+/// rotated: `errors.journal` becomes `errors.journal.1`, `errors.journal.1`
+/// becomes `errors.journal.2`, and so on, up to [`keep`](Self::keep) files.
+#[derive(Debug)]
+pub struct Writer {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    len: u64,
+}
+
+impl Writer {
+    /// Open (or create) the journal at `path`, capping each file at `max_bytes`.
+    ///
+    /// By default, one rotated file is kept.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.into();
+        let file = append(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            keep: 1,
+            file,
+            len,
+        })
+    }
+
+    /// Keep `keep` rotated files, in addition to the current one.
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Record `e`, timestamped now.
+    pub fn append(&mut self, e: &io::Error) -> io::Result<()> {
+        self.append_record(&ErrorRecord::new(e))
+    }
+
+    /// Record `record`.
+    pub fn append_record(&mut self, record: &ErrorRecord) -> io::Result<()> {
+        let line = record.to_line();
+        if self.len > 0 && self.len + line.len() as u64 > self.max_bytes {
+            self.rotate()?
+        }
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|()| self.file.sync_data())
+            .map_err(|e| e.context(format_args!("appending to {}", self.path.display())))?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotate = || {
+            match self.keep {
+                0 => fs::remove_file(&self.path)?,
+                keep => {
+                    for n in (1..keep).rev() {
+                        match fs::rename(rotated(&self.path, n), rotated(&self.path, n + 1)) {
+                            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                            _ => {}
+                        }
+                    }
+                    fs::rename(&self.path, rotated(&self.path, 1))?
+                }
+            }
+            append(&self.path)
+        };
+        self.file =
+            rotate().map_err(|e| e.context(format_args!("rotating {}", self.path.display())))?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.context(format_args!("opening journal {}", path.display())))
+}
+
+/// The path of the `n`th rotated file.
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{}", n));
+    path.into()
+}
+
+/// Reads [`ErrorRecord`]s from a journal file.
+///
+/// A truncated final record (as left by a crash) is skipped.
+/// Records from older versions of this crate, which lack fields, are still read.
+///
+/// ```
+/// use std::io;
+/// use io_extra::journal::Reader;
+///
+/// let record = Reader::new(&b"v1\t0\tNotFound\tno such table\n"[..]).next().unwrap()?;
+/// assert_eq!(record.kind, io::ErrorKind::NotFound);
+/// assert!(record.fields.is_empty());
+/// # Ok::<_, io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+    line: String,
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Read records from `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: String::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = io::Result<ErrorRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.inner.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => match self.line.strip_suffix('\n') {
+                    Some("") => continue,
+                    Some(line) => return Some(ErrorRecord::from_line(line)),
+                    None => return None,
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Read every record in the journal at `path`, including rotated files,
+/// oldest first.
+///
+/// ```
+/// use std::io;
+/// use io_extra::journal;
+///
+/// let dir = std::env::temp_dir().join(format!("io-extra-read-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir)?;
+/// let path = dir.join("errors.journal");
+///
+/// // Rotate on every record.
+/// let mut writer = journal::Writer::open(&path, 1)?.keep(2);
+/// for message in ["first", "second", "third", "fourth"] {
+///     writer.append(&io::Error::other(message))?;
+/// }
+///
+/// let messages = journal::read(&path)
+///     .map(|it| it.map(|record| record.message))
+///     .collect::<io::Result<Vec<_>>>()?;
+/// assert_eq!(messages, ["second", "third", "fourth"]);
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn read(path: impl AsRef<Path>) -> impl Iterator<Item = io::Result<ErrorRecord>> {
+    let path = path.as_ref().to_owned();
+    let rotated = (1..)
+        .map(|n| rotated(&path, n))
+        .take_while(|it| it.exists())
+        .collect::<Vec<_>>();
+    rotated
+        .into_iter()
+        .rev()
+        .chain(iter::once(path))
+        .flat_map(|path| {
+            let (records, error) = match File::open(&path) {
+                Ok(file) => (Some(Reader::new(BufReader::new(file))), None),
+                Err(e) => (
+                    None,
+                    Some(Err(
+                        e.context(format_args!("opening journal {}", path.display()))
+                    )),
+                ),
+            };
+            records.into_iter().flatten().chain(error)
+        })
+}
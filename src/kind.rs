@@ -0,0 +1,206 @@
+use std::io::ErrorKind::{self, *};
+
+macro_rules! kinds {
+    ($($(#[$attr:meta])* $kind:ident,)*) => {
+        /// Every [`ErrorKind`] this crate knows by name.
+        pub(crate) const ALL: &[ErrorKind] = &[$($(#[$attr])* $kind,)*];
+
+        /// The [`Debug`](std::fmt::Debug) representation of `kind`, without
+        /// formatting it, e.g `"ConnectionReset"`.
+        ///
+        /// Kinds this crate doesn't know are named `"Other"`.
+        pub(crate) fn name(kind: ErrorKind) -> &'static str {
+            match kind {
+                $($(#[$attr])* $kind => stringify!($kind),)*
+                _ => "Other",
+            }
+        }
+    };
+}
+
+kinds! {
+    AddrInUse,
+    AddrNotAvailable,
+    AlreadyExists,
+    BrokenPipe,
+    ConnectionAborted,
+    ConnectionRefused,
+    ConnectionReset,
+    Interrupted,
+    InvalidData,
+    InvalidInput,
+    NotConnected,
+    NotFound,
+    OutOfMemory,
+    PermissionDenied,
+    TimedOut,
+    UnexpectedEof,
+    Unsupported,
+    WouldBlock,
+    WriteZero,
+    #[cfg(io_error_more)]
+    IsADirectory,
+    #[cfg(io_error_more)]
+    NotADirectory,
+    #[cfg(io_error_more)]
+    DirectoryNotEmpty,
+    #[cfg(io_error_more)]
+    ReadOnlyFilesystem,
+    #[cfg(io_error_more)]
+    FileTooLarge,
+    #[cfg(io_error_more)]
+    StorageFull,
+    #[cfg(io_error_more)]
+    NotSeekable,
+    #[cfg(io_error_invalid_filename)]
+    InvalidFilename,
+    #[cfg(io_error_more)]
+    TooManyLinks,
+    #[cfg(io_error_more)]
+    HostUnreachable,
+    #[cfg(io_error_more)]
+    NetworkDown,
+    #[cfg(io_error_more)]
+    NetworkUnreachable,
+    #[cfg(io_error_more)]
+    ResourceBusy,
+    #[cfg(io_error_more)]
+    Deadlock,
+    #[cfg(io_error_more)]
+    StaleNetworkFileHandle,
+    #[cfg(io_error_quota_exceeded)]
+    QuotaExceeded,
+    #[cfg(io_error_crosses_devices)]
+    CrossesDevices,
+    #[cfg(io_error_more)]
+    ArgumentListTooLong,
+    #[cfg(feature = "nightly")]
+    FilesystemLoop,
+    Other,
+}
+/// Define `NAME` constants for kinds which are newer than our oldest supported
+/// compiler, or unstable, as the given fallback kind when they are unavailable,
+/// for mapping foreign codes to kinds.
+#[cfg(any(feature = "nfs", feature = "sftp", feature = "smb"))]
+macro_rules! newer_kinds {
+    ($(#[cfg($cfg:meta)] $name:ident = $kind:ident else $fallback:ident;)*) => {$(
+        #[cfg($cfg)]
+        const $name: std::io::ErrorKind = std::io::ErrorKind::$kind;
+        #[cfg(not($cfg))]
+        const $name: std::io::ErrorKind = std::io::ErrorKind::$fallback;
+    )*};
+}
+#[cfg(any(feature = "nfs", feature = "sftp", feature = "smb"))]
+pub(crate) use newer_kinds;
+
+/// Define a protocol's status codes as constants, their mapping to kinds, and
+/// an error payload type which keeps the original code, for protocols which
+/// send it back over the wire.
+///
+/// `display` and `unknown` format a known code's name and value, and an unknown
+/// code's value, respectively, with and without `defmt`.
+/// The importing module defines the reverse mapping, `status(kind)`.
+#[cfg(any(feature = "nfs", feature = "smb"))]
+macro_rules! statuses {
+    (
+        $(#[$ty_meta:meta])*
+        pub struct $ty:ident;
+
+        $(#[$success_meta:meta])*
+        $success:ident = $success_code:literal;
+
+        display = $display:literal, $defmt_display:literal;
+        unknown = $unknown:literal, $defmt_unknown:literal;
+
+        $(
+            $(#[$meta:meta])*
+            $name:ident = $code:literal => $kind:ident;
+        )*
+    ) => {
+        $(#[$success_meta])*
+        pub const $success: u32 = $success_code;
+
+        $(
+            $(#[$meta])*
+            pub const $name: u32 = $code;
+        )*
+
+        fn name(status: u32) -> Option<&'static str> {
+            match status {
+                $($code => Some(stringify!($name)),)*
+                _ => None,
+            }
+        }
+
+        #[doc = concat!(
+            "The [`ErrorKind`](std::io::ErrorKind) for a status, or [`None`] for [`",
+            stringify!($success),
+            "`].",
+        )]
+        ///
+        /// Unknown codes are mapped to [`ErrorKind::Other`](std::io::ErrorKind::Other).
+        pub fn kind(status: u32) -> Option<std::io::ErrorKind> {
+            match status {
+                $success => None,
+                $($code => Some($kind),)*
+                _ => Some(std::io::ErrorKind::Other),
+            }
+        }
+
+        $(#[$ty_meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $ty(pub u32);
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match name(self.0) {
+                    Some(name) => write!(f, $display, name, self.0),
+                    None => write!(f, $unknown, self.0),
+                }
+            }
+        }
+
+        impl std::error::Error for $ty {}
+
+        #[cfg(feature = "defmt")]
+        impl defmt::Format for $ty {
+            fn format(&self, f: defmt::Formatter<'_>) {
+                match name(self.0) {
+                    Some(name) => defmt::write!(f, $defmt_display, name, self.0),
+                    None => defmt::write!(f, $defmt_unknown, self.0),
+                }
+            }
+        }
+
+        /// See [`error`].
+        impl From<$ty> for std::io::Error {
+            fn from(status: $ty) -> Self {
+                error(status.0)
+            }
+        }
+
+        /// Create an error from a status, preserving it for [`status_of`].
+        ///
+        /// The status is kept even after context is attached, so that it can be
+        /// reported verbatim when the error is sent back over the wire.
+        pub fn error(status: u32) -> std::io::Error {
+            std::io::Error::new(kind(status).unwrap_or(std::io::ErrorKind::Other), $ty(status))
+        }
+
+        /// The status that `e` was [created](error) from, or else the one for its kind.
+        pub fn status_of(e: &std::io::Error) -> u32 {
+            $crate::context::errors(e)
+                .find_map(|it| it.downcast_ref::<$ty>())
+                .map(|it| it.0)
+                .unwrap_or_else(|| status(e.kind()))
+        }
+    };
+}
+#[cfg(any(feature = "nfs", feature = "smb"))]
+pub(crate) use statuses;
+
+/// Parse the [`Debug`](std::fmt::Debug) representation of an [`ErrorKind`],
+/// e.g `"ConnectionReset"`.
+pub(crate) fn from_name(name: &str) -> Option<ErrorKind> {
+    ALL.iter().copied().find(|kind| self::name(*kind) == name)
+}
@@ -0,0 +1,128 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+/// A typed view of common [`io::Error`]s, carrying any structured metadata this
+/// crate has attached to them, so callers can match on variants rather than
+/// strings.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{IoErrorExt as _, KnownError};
+///
+/// let e = io::Error::not_found("no such user").context("logging in");
+/// match KnownError::try_from(&e) {
+///     Ok(KnownError::NotFound { .. }) => {}
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(
+///     KnownError::try_from(&io::Error::other("oops")).unwrap_err(),
+///     io::ErrorKind::Other,
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KnownError {
+    /// [`ErrorKind::NotFound`].
+    NotFound {
+        /// The path which was not found, if [recorded](crate::IoErrorExt::with_path).
+        path: Option<PathBuf>,
+    },
+    /// [`ErrorKind::PermissionDenied`].
+    PermissionDenied {
+        /// The path which could not be accessed, if [recorded](crate::IoErrorExt::with_path).
+        path: Option<PathBuf>,
+    },
+    /// [`ErrorKind::AlreadyExists`].
+    AlreadyExists {
+        /// The path which already exists, if [recorded](crate::IoErrorExt::with_path).
+        path: Option<PathBuf>,
+    },
+    /// [`ErrorKind::TimedOut`].
+    Timeout {
+        /// How long the operation ran for, if recorded (see [`elapsed_of`](crate::elapsed_of)).
+        elapsed: Option<Duration>,
+    },
+    /// [`ErrorKind::ConnectionRefused`].
+    ConnectionRefused,
+    /// [`ErrorKind::ConnectionReset`].
+    ConnectionReset,
+    /// [`ErrorKind::BrokenPipe`].
+    BrokenPipe,
+    /// [`ErrorKind::UnexpectedEof`].
+    UnexpectedEof,
+    /// [`ErrorKind::InvalidData`].
+    InvalidData,
+    /// [`ErrorKind::InvalidInput`].
+    InvalidInput,
+}
+
+/// Fails with the [`ErrorKind`] of errors which don't have a variant.
+impl TryFrom<&io::Error> for KnownError {
+    type Error = ErrorKind;
+
+    fn try_from(e: &io::Error) -> Result<Self, Self::Error> {
+        let path = || crate::path(e).map(PathBuf::from);
+        Ok(match e.kind() {
+            ErrorKind::NotFound => Self::NotFound { path: path() },
+            ErrorKind::PermissionDenied => Self::PermissionDenied { path: path() },
+            ErrorKind::AlreadyExists => Self::AlreadyExists { path: path() },
+            ErrorKind::TimedOut => Self::Timeout {
+                elapsed: crate::elapsed_of(e),
+            },
+            ErrorKind::ConnectionRefused => Self::ConnectionRefused,
+            ErrorKind::ConnectionReset => Self::ConnectionReset,
+            ErrorKind::BrokenPipe => Self::BrokenPipe,
+            ErrorKind::UnexpectedEof => Self::UnexpectedEof,
+            ErrorKind::InvalidData => Self::InvalidData,
+            ErrorKind::InvalidInput => Self::InvalidInput,
+            other => return Err(other),
+        })
+    }
+}
+
+impl KnownError {
+    /// The [`ErrorKind`] of this variant.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotFound { .. } => ErrorKind::NotFound,
+            Self::PermissionDenied { .. } => ErrorKind::PermissionDenied,
+            Self::AlreadyExists { .. } => ErrorKind::AlreadyExists,
+            Self::Timeout { .. } => ErrorKind::TimedOut,
+            Self::ConnectionRefused => ErrorKind::ConnectionRefused,
+            Self::ConnectionReset => ErrorKind::ConnectionReset,
+            Self::BrokenPipe => ErrorKind::BrokenPipe,
+            Self::UnexpectedEof => ErrorKind::UnexpectedEof,
+            Self::InvalidData => ErrorKind::InvalidData,
+            Self::InvalidInput => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+/// The description of the kind, followed by any metadata.
+impl fmt::Display for KnownError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind())?;
+        match self {
+            Self::NotFound { path: Some(path) }
+            | Self::PermissionDenied { path: Some(path) }
+            | Self::AlreadyExists { path: Some(path) } => write!(f, ": {}", path.display()),
+            Self::Timeout {
+                elapsed: Some(elapsed),
+            } => write!(f, " after {:.1}s", elapsed.as_secs_f64()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Error for KnownError {}
+
+impl From<KnownError> for io::Error {
+    fn from(e: KnownError) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// The layer of a protocol stack which produced an error.
+///
+/// Stacks like TLS-over-TCP-over-proxy can tag errors with
+/// [`IoErrorExt::with_layer`](crate::IoErrorExt::with_layer), so that retry
+/// logic can differ per layer without parsing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Layer {
+    /// The underlying connection, e.g TCP.
+    Transport,
+    /// The protocol spoken over the connection, e.g TLS or HTTP.
+    Protocol,
+    /// The application's own logic.
+    Application,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layer::Transport => "transport",
+            Layer::Protocol => "protocol",
+            Layer::Application => "application",
+        })
+    }
+}
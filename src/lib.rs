@@ -14,8 +14,11 @@
 //!                                   // ^ easily add context
 //! }
 //! ```
-use sealed::Sealed;
+#![cfg_attr(feature = "nightly", feature(error_reporter, io_error_more))]
+
+use sealed::{Sealed, SealedOption, SealedResult};
 use std::{
+    any::Any,
     error::Error,
     fmt,
     io::{
@@ -27,20 +30,232 @@ use std::{
             Unsupported, WouldBlock, WriteZero,
         },
     },
+    path::PathBuf,
 };
 
 #[doc(inline)]
-pub use context::{context, with};
+pub use adapter::{
+    ContextReader, ContextWriter, Named, Newline, NormalizeNewlines, OptionalTail, ReadExt,
+    TrackedReader, WriteExt,
+};
+#[doc(inline)]
+pub use as_io::AsIoError;
+#[doc(inline)]
+pub use budget::{set_context_budget, suppressed_contexts};
+#[doc(inline)]
+pub use builder::ErrorBuilder;
+#[cfg(feature = "anyhow")]
+#[doc(inline)]
+pub use classify::from_anyhow;
+#[doc(inline)]
+pub use classify::{
+    absurd_io, classify, classify_message, infallible_io, into_io, kind_priority, reclassify_other,
+    register_classifier, set_kind_priority, Classifier,
+};
+#[doc(inline)]
+pub use comparable::ComparableError;
+#[cfg(feature = "camino")]
+#[doc(inline)]
+pub use context::utf8_path;
+#[doc(inline)]
+pub use context::{
+    chain, context, downcast_source, fields, os_error_code, path, payload_of, root_cause,
+    shared_context, try_downcast_source, with, Chain,
+};
+#[cfg(feature = "encoding_rs")]
+#[doc(inline)]
+pub use decode::DecodedReader;
+#[doc(inline)]
+pub use defer::{defer_io, take_deferred_errors, DeferIo};
+#[doc(inline)]
+pub use display::{ContextDisplay, Frame, Headline};
+#[cfg(feature = "defmt")]
+#[doc(inline)]
+pub use embedded::Defmt;
+#[doc(inline)]
+pub use help::{help_of, hints, register_help};
+#[doc(inline)]
+pub use intern::{interning_stats, set_context_interning, InternStats};
+#[doc(inline)]
+pub use known::KnownError;
+#[doc(inline)]
+pub use layer::Layer;
+#[doc(inline)]
+pub use lint::set_lint_hook;
+#[doc(inline)]
+pub use multi::{partition_io, MultiError};
+#[cfg(all(feature = "fs", feature = "net"))]
+#[doc(inline)]
+pub use open::{open_sink, open_source, register_scheme, Scheme};
+#[doc(inline)]
+pub use read::{
+    expect_eof, fill_buf_ctx, fill_nonempty, read_exact_ctx, read_growing, read_to_string_lossy_io,
+    read_to_string_strict,
+};
+#[doc(inline)]
+pub use report::{install_panic_hook, Report};
+#[doc(inline)]
+pub use sanitize::{Redact, Sanitized, Sanitizer};
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use serial::SerializableError;
+#[doc(inline)]
+pub use slice::{get_io, split_at_io};
+#[doc(inline)]
+pub use static_error::StaticError;
+#[doc(inline)]
+pub use stats::{dump_thread_error_stats, set_thread_error_stats};
+#[doc(inline)]
+pub use timed::{elapsed_of, limit_of, timed, timed_async, timed_out_after, with_deadline};
+#[doc(inline)]
+pub use verbosity::{context_v, context_verbosity, set_context_verbosity};
 
+#[macro_use]
+mod macros;
+
+mod adapter;
+pub mod alloc;
+mod as_io;
+mod budget;
+mod builder;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+mod classify;
+mod comparable;
 mod context;
+#[cfg(feature = "encoding_rs")]
+mod decode;
+mod defer;
+mod display;
+#[cfg(feature = "defmt")]
+mod embedded;
+#[cfg(feature = "fs")]
+pub mod fs;
+mod help;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "http_object_store")]
+pub mod http_object_store;
+mod intern;
+pub mod journal;
+mod kind;
+mod known;
+mod layer;
+mod lint;
+#[cfg(feature = "ndjson")]
+pub mod log;
+mod multi;
+#[cfg(feature = "net")]
+pub mod net;
+#[cfg(feature = "nfs")]
+pub mod nfs;
+#[cfg(feature = "notify")]
+pub mod notify;
+#[cfg(all(feature = "fs", feature = "net"))]
+mod open;
+pub mod prelude;
+mod read;
+mod report;
+#[cfg(feature = "retry")]
+pub mod retry;
+mod sanitize;
+#[cfg(feature = "serde")]
+mod serial;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+mod slice;
+#[cfg(feature = "smb")]
+pub mod smb;
+#[cfg(feature = "sql")]
+pub mod sql;
+mod static_error;
+mod stats;
+#[cfg(feature = "test")]
+pub mod test;
+mod timed;
+mod verbosity;
 
 mod sealed {
-    pub trait Sealed: Into<std::io::Error> {}
+    pub trait Sealed: Into<std::io::Error> {
+        fn as_io_error(&self) -> &std::io::Error;
+    }
+    pub trait SealedResult<T>: Into<std::io::Result<T>> {}
+    pub trait SealedOption<T>: Into<Option<T>> {}
+}
+
+/// Invoke `$callback` with every kind this crate has shorthands for, as
+/// `constructor, context_method, err_function -> Kind`, preceded by any `$extra`
+/// tokens.
+///
+/// Kinds which are newer than our oldest supported compiler are `#[cfg]`-ed on
+/// the probes in `build.rs`.
+macro_rules! for_each_kind {
+    ($callback:ident $(, $extra:tt)*) => {
+        $callback! {
+            $($extra)*
+            addr_in_use, addr_in_use_context, err_addr_in_use -> AddrInUse,
+            addr_not_available, addr_not_available_context, err_addr_not_available -> AddrNotAvailable,
+            already_exists, already_exists_context, err_already_exists -> AlreadyExists,
+            broken_pipe, broken_pipe_context, err_broken_pipe -> BrokenPipe,
+            connection_aborted, connection_aborted_context, err_connection_aborted -> ConnectionAborted,
+            connection_refused, connection_refused_context, err_connection_refused -> ConnectionRefused,
+            connection_reset, connection_reset_context, err_connection_reset -> ConnectionReset,
+            interrupted, interrupted_context, err_interrupted -> Interrupted,
+            invalid_data, invalid_data_context, err_invalid_data -> InvalidData,
+            invalid_input, invalid_input_context, err_invalid_input -> InvalidInput,
+            not_connected, not_connected_context, err_not_connected -> NotConnected,
+            not_found, not_found_context, err_not_found -> NotFound,
+            out_of_memory, out_of_memory_context, err_out_of_memory -> OutOfMemory,
+            permission_denied, permission_denied_context, err_permission_denied -> PermissionDenied,
+            timed_out, timed_out_context, err_timed_out -> TimedOut,
+            unexpected_eof, unexpected_eof_context, err_unexpected_eof -> UnexpectedEof,
+            unsupported, unsupported_context, err_unsupported -> Unsupported,
+            would_block, would_block_context, err_would_block -> WouldBlock,
+            write_zero, write_zero_context, err_write_zero -> WriteZero,
+            #[cfg(io_error_more)]
+            is_a_directory, is_a_directory_context, err_is_a_directory -> io::ErrorKind::IsADirectory,
+            #[cfg(io_error_more)]
+            not_a_directory, not_a_directory_context, err_not_a_directory -> io::ErrorKind::NotADirectory,
+            #[cfg(io_error_more)]
+            directory_not_empty, directory_not_empty_context, err_directory_not_empty -> io::ErrorKind::DirectoryNotEmpty,
+            #[cfg(io_error_more)]
+            read_only_filesystem, read_only_filesystem_context, err_read_only_filesystem -> io::ErrorKind::ReadOnlyFilesystem,
+            #[cfg(io_error_more)]
+            file_too_large, file_too_large_context, err_file_too_large -> io::ErrorKind::FileTooLarge,
+            #[cfg(io_error_more)]
+            storage_full, storage_full_context, err_storage_full -> io::ErrorKind::StorageFull,
+            #[cfg(io_error_more)]
+            not_seekable, not_seekable_context, err_not_seekable -> io::ErrorKind::NotSeekable,
+            #[cfg(io_error_invalid_filename)]
+            invalid_filename, invalid_filename_context, err_invalid_filename -> io::ErrorKind::InvalidFilename,
+            #[cfg(io_error_more)]
+            too_many_links, too_many_links_context, err_too_many_links -> io::ErrorKind::TooManyLinks,
+            #[cfg(io_error_more)]
+            host_unreachable, host_unreachable_context, err_host_unreachable -> io::ErrorKind::HostUnreachable,
+            #[cfg(io_error_more)]
+            network_down, network_down_context, err_network_down -> io::ErrorKind::NetworkDown,
+            #[cfg(io_error_more)]
+            network_unreachable, network_unreachable_context, err_network_unreachable -> io::ErrorKind::NetworkUnreachable,
+            #[cfg(io_error_more)]
+            resource_busy, resource_busy_context, err_resource_busy -> io::ErrorKind::ResourceBusy,
+            #[cfg(io_error_more)]
+            deadlock, deadlock_context, err_deadlock -> io::ErrorKind::Deadlock,
+            #[cfg(io_error_more)]
+            stale_network_file_handle, stale_network_file_handle_context, err_stale_network_file_handle -> io::ErrorKind::StaleNetworkFileHandle,
+            #[cfg(io_error_quota_exceeded)]
+            quota_exceeded, quota_exceeded_context, err_quota_exceeded -> io::ErrorKind::QuotaExceeded,
+            #[cfg(io_error_crosses_devices)]
+            crosses_devices, crosses_devices_context, err_crosses_devices -> io::ErrorKind::CrossesDevices,
+            #[cfg(io_error_more)]
+            argument_list_too_long, argument_list_too_long_context, err_argument_list_too_long -> io::ErrorKind::ArgumentListTooLong,
+        }
+    };
 }
 
 macro_rules! ctor {
-    ($($name:ident -> $kind:expr),* $(,)?) => {
+    ($($(#[$attr:meta])* $name:ident, $_context:ident, $_err:ident -> $kind:expr),* $(,)?) => {
         $(
+            $(#[$attr])*
             #[doc = concat!(
                 "Create an [`io::Error`] with kind [`",
                 stringify!($kind),
@@ -53,6 +268,90 @@ macro_rules! ctor {
     };
 }
 
+macro_rules! kind_context {
+    ($($(#[$attr:meta])* $_name:ident, $context:ident, $_err:ident -> $kind:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!(
+                "Change the kind of the error to [`",
+                stringify!($kind),
+                "`], attaching a message.",
+            )]
+            // e.g `is_a_directory_context`.
+            #[allow(clippy::wrong_self_convention)]
+            fn $context(self, msg: impl fmt::Display) -> io::Result<T> {
+                self.into().map_err(|e| context::kind_context(e, $kind, msg))
+            }
+        )*
+    };
+}
+
+/// Define `$name!` to format a message for `__private::$name`.
+///
+/// `$d` is a `$`, as nested macros can't otherwise refer to their own arguments.
+macro_rules! format_macro {
+    ($d:tt $($(#[$attr:meta])* $name:ident, $_context:ident, $_err:ident -> $kind:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!(
+                "Create an [`io::Error`](std::io::Error) with kind [`",
+                stringify!($kind),
+                "`], and a message from [`format_args!`].",
+            )]
+            ///
+            /// The message is only copied if it has arguments.
+            #[macro_export]
+            macro_rules! $name {
+                ($d($d arg:tt)+) => {
+                    $d crate::__private::$name(::std::format_args!($d($d arg)+))
+                };
+            }
+        )*
+    };
+}
+
+macro_rules! format_ctor {
+    ($($(#[$attr:meta])* $name:ident, $_context:ident, $_err:ident -> $kind:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            pub fn $name(args: fmt::Arguments<'_>) -> io::Error {
+                crate::static_error::format_error($kind, args)
+            }
+        )*
+    };
+}
+
+macro_rules! err_fn {
+    ($($(#[$attr:meta])* $_name:ident, $_context:ident, $err:ident -> $kind:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!(
+                "Fail with an [`io::Error`] with kind [`",
+                stringify!($kind),
+                "`], wrapping the passed in `error`.",
+            )]
+            pub fn $err<T>(error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Result<T> {
+                Err(io::Error::new($kind, error))
+            }
+        )*
+    };
+}
+
+for_each_kind!(format_macro, $);
+for_each_kind!(err_fn);
+
+#[doc(hidden)]
+pub mod __private {
+    use std::{
+        fmt,
+        io::{self, ErrorKind::*},
+    };
+
+    pub use crate::context::context_fields;
+
+    for_each_kind!(format_ctor);
+}
+
 /// An extension trait for [`io::Error`], with shorthand constructors for various
 /// [`io::ErrorKind`]s.
 ///
@@ -76,29 +375,40 @@ macro_rules! ctor {
 /// }
 /// ```
 ///
+/// Each constructor has a macro of the same name which takes a format string:
+///
+/// ```
+/// use std::io;
+///
+/// let tag = 7;
+/// let e = io_extra::invalid_data!("unexpected tag {tag}");
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(e.to_string(), "unexpected tag 7");
+/// ```
+///
+/// There are also functions like [`err_invalid_data`] for returning early from
+/// functions generic over the success type:
+///
+/// ```
+/// use std::io;
+/// use io_extra::err_not_found;
+///
+/// fn lookup<T: Default>(key: &str) -> io::Result<T> {
+///     match key {
+///         "default" => Ok(T::default()),
+///         _ => err_not_found(format!("no entry {}", key)),
+///     }
+/// }
+/// assert_eq!(lookup::<u8>("other").unwrap_err().kind(), io::ErrorKind::NotFound);
+/// ```
+///
+/// Constructors for the newer filesystem, network and resource kinds, like
+/// [`StorageFull`](io::ErrorKind::StorageFull), are only available if the compiler
+/// has them.
+///
 /// This trait is _sealed_, and cannot be implemented by types outside this library.
 pub trait IoErrorExt: Sealed {
-    ctor! {
-        addr_in_use -> AddrInUse,
-        addr_not_available -> AddrNotAvailable,
-        already_exists -> AlreadyExists,
-        broken_pipe -> BrokenPipe,
-        connection_aborted -> ConnectionAborted,
-        connection_refused -> ConnectionRefused,
-        connection_reset -> ConnectionReset,
-        interrupted -> Interrupted,
-        invalid_data -> InvalidData,
-        invalid_input -> InvalidInput,
-        not_connected -> NotConnected,
-        not_found -> NotFound,
-        out_of_memory -> OutOfMemory,
-        permission_denied -> PermissionDenied,
-        timed_out -> TimedOut,
-        unexpected_eof -> UnexpectedEof,
-        unsupported -> Unsupported,
-        would_block -> WouldBlock,
-        write_zero -> WriteZero,
-    }
+    for_each_kind!(ctor);
     /// Attach a message to this error.
     fn context(self, msg: impl fmt::Display) -> io::Error {
         context(self.into(), msg)
@@ -111,7 +421,569 @@ pub trait IoErrorExt: Sealed {
     fn io_context(self, msg: impl fmt::Display) -> io::Error {
         self.context(msg)
     }
+    /// Attach a message to this error, with structured fields.
+    ///
+    /// The fields are rendered after the message, and may be retrieved later
+    /// with [`fields`].
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{fields, IoErrorExt as _};
+    ///
+    /// let (addr, attempt) = ("10.0.0.1:80", 2);
+    /// let e = io::Error::connection_refused("refused")
+    ///     .context_fields("opening socket", [("addr", addr.to_string()), ("attempt", attempt.to_string())]);
+    /// assert_eq!(e.to_string(), "opening socket (addr=10.0.0.1:80, attempt=2)");
+    /// assert_eq!(
+    ///     format!("{:#}", e),
+    ///     "opening socket (addr=10.0.0.1:80, attempt=2): refused",
+    /// );
+    /// assert_eq!(
+    ///     fields(&e).collect::<Vec<_>>(),
+    ///     [("addr", "10.0.0.1:80"), ("attempt", "2")],
+    /// );
+    /// ```
+    fn context_fields<K: Into<String>, V: fmt::Display>(
+        self,
+        msg: impl fmt::Display,
+        fields: impl IntoIterator<Item = (K, V)>,
+    ) -> io::Error {
+        context::displayed_fields(self.into(), msg, fields)
+    }
+    /// Attach a message to this error, if `level` is within the
+    /// [verbosity](set_context_verbosity).
+    ///
+    /// See [`context_v`].
+    fn context_v(self, level: u8, msg: impl fmt::Display) -> io::Error {
+        context_v(self.into(), level, msg)
+    }
+    /// Attach a message to this error, built by `f`.
+    ///
+    /// `f` is not called if the message would be discarded by the
+    /// [context budget](set_context_budget).
+    fn with_context<D: fmt::Display>(self, f: impl FnOnce() -> D) -> io::Error {
+        context::lazy_context(self.into(), f)
+    }
+    /// Reinterpret this error as `kind`, keeping its message, context and sources.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{root_cause, IoErrorExt as _};
+    ///
+    /// let e = io::Error::from_raw_os_error(2)
+    ///     .context("looking up profile \"ferris\"")
+    ///     .with_kind(io::ErrorKind::InvalidInput);
+    /// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    /// assert!(format!("{:#}", e).starts_with("looking up profile \"ferris\": "));
+    /// assert!(root_cause(&e).is::<io::Error>());
+    ///
+    /// let e = io::Error::from(io::ErrorKind::NotFound).with_kind(io::ErrorKind::InvalidInput);
+    /// assert_eq!(e.to_string(), "entity not found");
+    /// ```
+    ///
+    /// See also the `*_context` methods on [`IoResultExt`], which also attach a message.
+    fn with_kind(self, kind: io::ErrorKind) -> io::Error {
+        context::with_kind(self.into(), kind)
+    }
+    /// Iterate over this error and its sources, outermost first.
+    ///
+    /// See [`chain`].
+    fn chain(&self) -> Chain<'_> {
+        chain(self.as_io_error())
+    }
+    /// The OS error code of this error, looking through any context.
+    ///
+    /// See [`os_error_code`].
+    fn os_error_code(&self) -> Option<i32> {
+        os_error_code(self.as_io_error())
+    }
+    /// A snapshot of this error which implements [`PartialEq`] and [`Hash`](std::hash::Hash).
+    fn comparable(&self) -> ComparableError {
+        ComparableError::from(self.as_io_error())
+    }
+    /// The innermost error in the chain, past any context.
+    ///
+    /// See [`root_cause`].
+    fn root_cause(&self) -> &(dyn Error + 'static) {
+        root_cause(self.as_io_error())
+    }
+    /// The outermost human-readable message of this error, without any causes
+    /// or OS error codes.
+    ///
+    /// This is suitable for user interfaces which need a one-liner,
+    /// while logs keep the full chain.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let e = io::Error::from_raw_os_error(2).context("couldn't open config");
+    /// assert_eq!(e.headline().to_string(), "couldn't open config");
+    /// ```
+    fn headline(&self) -> Headline<'_> {
+        Headline(self.as_io_error())
+    }
+    /// A bounded, sanitized description of this error, suitable for use as a
+    /// metrics label or alert title.
+    ///
+    /// This is the kind and the [`headline`](Self::headline), restricted to
+    /// ASCII alphanumerics and `-_.:/ `, and truncated to `max_len` characters.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let e = io::Error::not_found("no user \"ferris\"\nin table");
+    /// assert_eq!(e.summary(24), "NotFound: no user _ferri");
+    /// ```
+    fn summary(&self, max_len: usize) -> String {
+        display::summary(self.as_io_error(), max_len)
+    }
+    /// Returns `true` if this error is of kind [`NotFound`].
+    ///
+    /// The kind is preserved through any [`context`](Self::context).
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let e = io::Error::not_found("no such file").context("reading config");
+    /// assert!(e.is_not_found());
+    /// assert!(!e.is_timed_out());
+    /// ```
+    fn is_not_found(&self) -> bool {
+        self.as_io_error().kind() == NotFound
+    }
+    /// Returns `true` if this error is of kind [`PermissionDenied`].
+    fn is_permission_denied(&self) -> bool {
+        self.as_io_error().kind() == PermissionDenied
+    }
+    /// Returns `true` if this error is of kind [`AlreadyExists`].
+    fn is_already_exists(&self) -> bool {
+        self.as_io_error().kind() == AlreadyExists
+    }
+    /// Returns `true` if this error is of kind [`Interrupted`].
+    fn is_interrupted(&self) -> bool {
+        self.as_io_error().kind() == Interrupted
+    }
+    /// Returns `true` if this error is of kind [`TimedOut`].
+    fn is_timed_out(&self) -> bool {
+        self.as_io_error().kind() == TimedOut
+    }
+    /// Returns `true` if this error is of kind [`WouldBlock`].
+    fn is_would_block(&self) -> bool {
+        self.as_io_error().kind() == WouldBlock
+    }
+    /// Returns `true` if this error is of kind [`UnexpectedEof`].
+    fn is_unexpected_eof(&self) -> bool {
+        self.as_io_error().kind() == UnexpectedEof
+    }
+    /// Returns `true` if this error is a failure of the connection itself:
+    /// [`ConnectionReset`], [`ConnectionAborted`], [`ConnectionRefused`] or
+    /// [`NotConnected`].
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// assert!(io::Error::connection_refused("nobody home").is_connection_error());
+    /// assert!(!io::Error::broken_pipe("hung up").is_connection_error());
+    /// ```
+    fn is_connection_error(&self) -> bool {
+        matches!(
+            self.as_io_error().kind(),
+            ConnectionReset | ConnectionAborted | ConnectionRefused | NotConnected
+        )
+    }
+    /// Returns `true` if this error just means that the peer went away.
+    ///
+    /// [`BrokenPipe`] is always benign.
+    /// [`ConnectionReset`], [`ConnectionAborted`] and [`UnexpectedEof`] are benign
+    /// if the connection was `idle`, i.e between complete messages of the protocol.
+    ///
+    /// Servers can use this to avoid logging clients which simply disconnected.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let e = io::Error::unexpected_eof("peer hung up").context("reading request");
+    /// assert!(e.is_benign_disconnect(true));
+    /// assert!(!e.is_benign_disconnect(false));
+    /// ```
+    fn is_benign_disconnect(&self, idle: bool) -> bool {
+        match self.as_io_error().kind() {
+            BrokenPipe => true,
+            ConnectionReset | ConnectionAborted | UnexpectedEof => idle,
+            _ => false,
+        }
+    }
+    /// Join this error from a fast path with `fallback`, the error from the
+    /// slower path which was tried next, so that neither failure is discarded.
+    ///
+    /// The joined error has the kind of `fallback`, the last thing tried.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let mmap = io::Error::unsupported("mmap unavailable").context("mapping index");
+    /// let read = io::Error::not_found("no such file").context("reading index");
+    /// let e = mmap.or_fallback(read).context("loading index");
+    ///
+    /// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    /// assert_eq!(
+    ///     format!("{:#}", e),
+    ///     "loading index: primary attempt failed: mapping index: mmap unavailable; \
+    ///      fallback also failed: reading index: no such file",
+    /// );
+    /// ```
+    fn or_fallback(self, fallback: io::Error) -> io::Error {
+        io::Error::new(
+            fallback.kind(),
+            multi::Fallback {
+                primary: self.into(),
+                fallback,
+            },
+        )
+    }
+    /// Tag this error with the [`Layer`] which produced it.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, Layer};
+    ///
+    /// let e = io::Error::connection_reset("peer reset")
+    ///     .with_layer(Layer::Transport)
+    ///     .context("fetching index");
+    /// assert_eq!(e.layer(), Some(Layer::Transport));
+    /// ```
+    fn with_layer(self, layer: Layer) -> io::Error {
+        context::with_frame(self.into(), |frame| frame.layer = Some(layer))
+    }
+    /// The innermost (i.e originating) [`Layer`] this error was tagged with.
+    fn layer(&self) -> Option<Layer> {
+        context::frames(self.as_io_error())
+            .filter_map(|frame| frame.layer)
+            .last()
+    }
+    /// Carry an arbitrary value with this error, to be retrieved with [`payload_of`].
+    ///
+    /// This is an escape hatch for rich domain data (request IDs, retry state)
+    /// which must pass through APIs constrained to [`io::Error`].
+    /// The value is not displayed, and survives any context attached on the
+    /// way up:
+    ///
+    /// ```
+    /// use std::{io, time::Duration};
+    /// use io_extra::{payload_of, IoErrorExt as _};
+    ///
+    /// struct RetryHint {
+    ///     after: Duration,
+    /// }
+    ///
+    /// let e = io::Error::would_block("throttled")
+    ///     .with_payload(RetryHint { after: Duration::from_secs(2) })
+    ///     .context("fetching page")
+    ///     .context("crawling");
+    /// let hint = payload_of::<RetryHint>(&e).unwrap();
+    /// assert_eq!(hint.after, Duration::from_secs(2));
+    /// ```
+    fn with_payload<T: Any + Send + Sync>(self, payload: T) -> io::Error {
+        context::with_frame(self.into(), |frame| frame.payloads.push(Box::new(payload)))
+    }
+    /// Attach the path of the file this error concerns, which std's errors lack.
+    ///
+    /// The path is displayed as a frame of context, and may be retrieved with
+    /// [`path`].
+    fn with_path(self, path: impl Into<PathBuf>) -> io::Error {
+        context::with_path(self.into(), path.into())
+    }
+    /// Attach a URL pointing to documentation on remediating this error.
+    ///
+    /// See [`help_of`].
+    fn with_help(self, url: impl Into<String>) -> io::Error {
+        let url = url.into();
+        context::with_frame(self.into(), |frame| frame.help = Some(url))
+    }
+    /// Attach actionable advice for resolving this error.
+    ///
+    /// Unlike [`context`](Self::context), this is not part of the chain of causes.
+    /// See [`hints`].
+    fn suggest(self, hint: impl fmt::Display) -> io::Error {
+        let hint = hint.to_string();
+        context::with_frame(self.into(), |frame| frame.hints.push(hint))
+    }
+    /// Display this error with every frame in the chain passed through `sanitizer`.
+    ///
+    /// See [`Redact`].
+    fn sanitized<'a, S: Sanitizer + ?Sized>(&'a self, sanitizer: &'a S) -> Sanitized<'a, S> {
+        Sanitized {
+            error: self.as_io_error(),
+            sanitizer,
+        }
+    }
+    /// Render this error with the standard library's [`Report`](std::error::Report),
+    /// listing each cause on its own line.
+    ///
+    /// See also the stable [`Report`].
+    ///
+    /// ```
+    /// #![feature(error_reporter)]
+    /// use std::io;
+    /// use io_extra::IoErrorExt as _;
+    ///
+    /// let e = io::Error::not_found("no such file").context("reading config");
+    /// assert_eq!(
+    ///     e.report().to_string(),
+    ///     "\
+    /// reading config
+    ///
+    /// Caused by:
+    ///       no such file"
+    /// );
+    /// ```
+    #[cfg(feature = "nightly")]
+    fn report(self) -> std::error::Report<io::Error> {
+        std::error::Report::new(self.into()).pretty(true)
+    }
 }
 
-impl Sealed for io::Error {}
+impl Sealed for io::Error {
+    fn as_io_error(&self) -> &io::Error {
+        self
+    }
+}
 impl IoErrorExt for io::Error {}
+
+/// An extension trait for [`io::Result`].
+///
+/// This trait is _sealed_, and cannot be implemented by types outside this library.
+pub trait IoResultExt<T>: SealedResult<T> {
+    /// Run `cleanup` regardless of whether this is an error.
+    ///
+    /// If both fail, the primary error is returned, with the cleanup failure
+    /// attached as a _suppressed_ error, visible in its [`Debug`](fmt::Debug)
+    /// representation.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, IoResultExt as _};
+    ///
+    /// let res: io::Result<()> = Err(io::Error::invalid_data("bad header"));
+    /// let e = res
+    ///     .also_cleanup(|| Err(io::Error::permission_denied("couldn't unlink tempfile")))
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    /// assert_eq!(e.to_string(), "bad header");
+    /// assert!(format!("{:?}", e).contains("couldn't unlink tempfile"));
+    /// ```
+    fn also_cleanup(self, cleanup: impl FnOnce() -> io::Result<()>) -> io::Result<T>;
+    /// Attach a message to the error, if any.
+    ///
+    /// The message is only formatted if there is an error.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, IoResultExt as _};
+    ///
+    /// fn load() -> io::Result<String> {
+    ///     Err(io::Error::not_found("no such file")).context("reading config")
+    /// }
+    /// assert_eq!(format!("{:#}", load().unwrap_err()), "reading config: no such file");
+    ///
+    /// let e = Err::<(), _>(io::Error::other("not a number"))
+    ///     .invalid_data_context("parsing port")
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    /// assert_eq!(format!("{:#}", e), "parsing port: not a number");
+    /// ```
+    fn context(self, msg: impl fmt::Display) -> io::Result<T> {
+        self.into().map_err(|e| context(e, msg))
+    }
+    /// Attach a message to the error, if any.
+    ///
+    /// Provided with a different name to not conflict with [`anyhow::Context`].
+    ///
+    /// [`anyhow::Context`]: (https://docs.rs/anyhow/1/anyhow/trait.Context.html#method.context).
+    fn io_context(self, msg: impl fmt::Display) -> io::Result<T> {
+        self.context(msg)
+    }
+    /// Attach the path of the file the error concerns, if any.
+    ///
+    /// See [`IoErrorExt::with_path`].
+    fn with_path(self, path: impl Into<PathBuf>) -> io::Result<T> {
+        self.into().map_err(|e| context::with_path(e, path.into()))
+    }
+    /// Attach a message to the error, if any, with structured fields.
+    ///
+    /// See [`IoErrorExt::context_fields`].
+    fn context_fields<K: Into<String>, V: fmt::Display>(
+        self,
+        msg: impl fmt::Display,
+        fields: impl IntoIterator<Item = (K, V)>,
+    ) -> io::Result<T> {
+        self.into()
+            .map_err(|e| context::displayed_fields(e, msg, fields))
+    }
+    /// Attach a message to the error, if any, and if `level` is within the
+    /// [verbosity](set_context_verbosity).
+    ///
+    /// See [`context_v`].
+    fn context_v(self, level: u8, msg: impl fmt::Display) -> io::Result<T> {
+        self.into().map_err(|e| context_v(e, level, msg))
+    }
+    /// Attach a message to the error, if any, built by `f`.
+    ///
+    /// Nothing is allocated on the `Ok` path.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::IoResultExt as _;
+    ///
+    /// let path = "/etc/app.toml";
+    /// let res = Ok::<_, io::Error>(()).with_context(|| -> String { unreachable!() });
+    /// assert!(res.is_ok());
+    ///
+    /// let e = Err::<(), _>(io::Error::other("disk on fire"))
+    ///     .with_context(|| format!("reading {}", path))
+    ///     .unwrap_err();
+    /// assert_eq!(format!("{:#}", e), "reading /etc/app.toml: disk on fire");
+    /// ```
+    fn with_context<D: fmt::Display>(self, f: impl FnOnce() -> D) -> io::Result<T> {
+        self.into().map_err(|e| context::lazy_context(e, f))
+    }
+    /// Turn errors of `kind` into [`Ok(None)`](None), for operations where that
+    /// failure is expected.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, IoResultExt as _};
+    ///
+    /// let res = Err::<(), _>(io::Error::already_exists("lock is held"));
+    /// assert!(res.ignore_kind(io::ErrorKind::AlreadyExists)?.is_none());
+    ///
+    /// let res = Err::<(), _>(io::Error::permission_denied("no"));
+    /// assert!(res.ignore_kind(io::ErrorKind::AlreadyExists).is_err());
+    /// # Ok::<_, io::Error>(())
+    /// ```
+    fn ignore_kind(self, kind: io::ErrorKind) -> io::Result<Option<T>> {
+        match self.into() {
+            Ok(it) => Ok(Some(it)),
+            Err(e) if e.kind() == kind => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    /// Turn [`NotFound`] errors into [`Ok(None)`](None), as when deleting a file
+    /// if it exists, or reading it if it is present.
+    ///
+    /// ```
+    /// use std::fs;
+    /// use io_extra::IoResultExt as _;
+    ///
+    /// assert!(fs::remove_file("/no/such/file").ignore_not_found()?.is_none());
+    /// assert!(fs::read("/no/such/config.toml").ignore_not_found()?.is_none());
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    fn ignore_not_found(self) -> io::Result<Option<T>> {
+        self.ignore_kind(NotFound)
+    }
+    for_each_kind!(kind_context);
+}
+
+impl<T> SealedResult<T> for io::Result<T> {}
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn also_cleanup(self, cleanup: impl FnOnce() -> io::Result<()>) -> io::Result<T> {
+        match (self, cleanup()) {
+            (Ok(it), Ok(())) => Ok(it),
+            (Ok(_), Err(secondary)) => Err(secondary),
+            (Err(primary), Ok(())) => Err(primary),
+            (Err(primary), Err(secondary)) => Err(context::suppress(primary, secondary)),
+        }
+    }
+}
+
+/// An extension trait for [`Option`], for converting failed lookups into
+/// [`io::Error`]s.
+///
+/// ```
+/// use std::{collections::HashMap, io};
+/// use io_extra::OptionIoExt as _;
+///
+/// let users = HashMap::from([(1, "ferris")]);
+/// let e = users.get(&2).ok_or_not_found("no user 2").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert_eq!(e.to_string(), "no user 2");
+///
+/// let e = "".chars().next().ok_or_kind(io::ErrorKind::InvalidInput, "empty name").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+/// ```
+///
+/// This trait is _sealed_, and cannot be implemented by types outside this library.
+pub trait OptionIoExt<T>: SealedOption<T> {
+    /// Fail with an [`io::Error`] of `kind`, wrapping `error`, if this is [`None`].
+    fn ok_or_kind(
+        self,
+        kind: io::ErrorKind,
+        error: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> io::Result<T> {
+        self.into().ok_or_else(|| io::Error::new(kind, error))
+    }
+    /// Fail with a [`NotFound`] error, wrapping `error`, if this is [`None`].
+    fn ok_or_not_found(self, error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Result<T> {
+        self.ok_or_kind(NotFound, error)
+    }
+}
+
+impl<T> SealedOption<T> for Option<T> {}
+impl<T> OptionIoExt<T> for Option<T> {}
+
+// The guarantees integrations may rely on, checked at compile time so that they
+// can't regress.
+const _: fn() = || {
+    fn error<T: Error + fmt::Display + fmt::Debug + Send + Sync + 'static + Into<io::Error>>() {}
+    fn into_io<T: fmt::Display + fmt::Debug + Send + Sync + 'static + Into<io::Error>>() {}
+    fn debug<T: fmt::Debug + Send + Sync>() {}
+    // Views of an error's sources, which needn't be `Sync`.
+    fn view<T: fmt::Debug + Clone>() {}
+
+    error::<MultiError>();
+    error::<KnownError>();
+    #[cfg(feature = "smb")]
+    error::<smb::NtStatus>();
+    #[cfg(feature = "nfs")]
+    error::<nfs::NfsStatus>();
+
+    into_io::<Report>();
+    into_io::<ComparableError>();
+    error::<StaticError>();
+    into_io::<journal::ErrorRecord>();
+    #[cfg(feature = "serde")]
+    into_io::<SerializableError>();
+
+    debug::<Named<std::fs::File>>();
+    debug::<ContextReader<std::fs::File>>();
+    debug::<ContextWriter<std::fs::File>>();
+    debug::<OptionalTail<std::fs::File>>();
+    debug::<Classifier>();
+    view::<Chain<'static>>();
+    debug::<Headline<'static>>();
+    view::<Frame<'static>>();
+    debug::<Sanitized<'static, Redact>>();
+    debug::<journal::Writer>();
+    debug::<journal::Reader<io::BufReader<std::fs::File>>>();
+    #[cfg(feature = "net")]
+    debug::<net::Duplex>();
+    #[cfg(feature = "test")]
+    debug::<test::ClosedReader>();
+    #[cfg(feature = "test")]
+    debug::<test::ClosedWriter>();
+    #[cfg(feature = "test")]
+    debug::<test::PendingReader>();
+    #[cfg(feature = "chaos")]
+    debug::<chaos::Chaos<std::fs::File>>();
+    #[cfg(feature = "ndjson")]
+    debug::<log::NdjsonSink<std::fs::File>>();
+    #[cfg(feature = "http")]
+    debug::<http::Problem<'static>>();
+};
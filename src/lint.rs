@@ -0,0 +1,111 @@
+use std::{
+    fmt::{self, Write as _},
+    io, str,
+    sync::RwLock,
+};
+
+/// Chains deeper than this are probably being re-wrapped in a loop.
+const MAX_DEPTH: usize = 32;
+
+type Hook = dyn Fn(&str) + Send + Sync;
+
+static HOOK: RwLock<Option<Box<Hook>>> = RwLock::new(None);
+
+/// Receive warnings about suspicious use of context.
+///
+/// With `debug_assertions`, and once a hook is set, attaching context warns if:
+/// - the message is the same as that of the error it wraps,
+/// - or the chain is suspiciously deep,
+///
+/// catching accidental re-wrapping during development.
+/// Otherwise, no checks are made.
+///
+/// Tests can make any such mistakes fatal:
+///
+/// ```
+/// use std::io;
+/// use io_extra::{set_lint_hook, IoErrorExt as _};
+///
+/// set_lint_hook(|warning| panic!("{}", warning));
+///
+/// let e = io::Error::other("disk on fire").context("reading config");
+/// // let e = e.context("reading config"); // would panic
+/// ```
+pub fn set_lint_hook(hook: impl Fn(&str) + Send + Sync + 'static) {
+    *HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(hook))
+}
+
+/// Warn if wrapping `e` in `context` looks like a mistake.
+pub(crate) fn check(e: &io::Error, context: &str) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let hook = HOOK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(hook) = &*hook else {
+        return;
+    };
+    let warning = match crate::chain(e).take(MAX_DEPTH).count() {
+        MAX_DEPTH => format!(
+            "context {:?} is being attached to a chain of at least {} errors",
+            context, MAX_DEPTH
+        ),
+        _ if headline_is(e, context) => format!(
+            "context {:?} repeats the message of the error it wraps",
+            context
+        ),
+        _ => return,
+    };
+    hook(&warning)
+}
+
+/// Whether the [`Headline`](crate::display::Headline) of `e` is `expected`,
+/// without allocating.
+fn headline_is(e: &io::Error, expected: &str) -> bool {
+    let mut sink = Matches {
+        rest: expected,
+        tail: [0; 32],
+        tail_len: 0,
+    };
+    if write!(sink, "{}", e).is_err() || !sink.rest.is_empty() {
+        return false;
+    }
+    let tail = str::from_utf8(&sink.tail[..sink.tail_len]).unwrap_or("?");
+    match (tail, e.raw_os_error()) {
+        ("", _) => true,
+        (tail, Some(code)) => tail
+            .strip_prefix(" (os error ")
+            .and_then(|it| it.strip_suffix(')'))
+            .is_some_and(|it| it.parse() == Ok(code)),
+        (_, None) => false,
+    }
+}
+
+/// Consumes output matching `rest`, keeping a short tail of anything after it,
+/// and failing on a mismatch.
+struct Matches<'a> {
+    rest: &'a str,
+    tail: [u8; 32],
+    tail_len: usize,
+}
+
+impl fmt::Write for Matches<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let extra = match self.rest.strip_prefix(s) {
+            Some(rest) => {
+                self.rest = rest;
+                return Ok(());
+            }
+            None => s.strip_prefix(self.rest).ok_or(fmt::Error)?,
+        };
+        self.rest = "";
+        let tail = self
+            .tail
+            .get_mut(self.tail_len..self.tail_len + extra.len())
+            .ok_or(fmt::Error)?;
+        tail.copy_from_slice(extra.as_bytes());
+        self.tail_len += extra.len();
+        Ok(())
+    }
+}
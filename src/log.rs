@@ -0,0 +1,120 @@
+//! Structured error logs, without adopting a logging framework.
+
+use std::io::{self, Write};
+
+use crate::{IoErrorExt as _, SerializableError};
+
+/// When an [`NdjsonSink`] flushes its writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum FlushPolicy {
+    /// Flush after every record, so that no records are lost on a crash.
+    #[default]
+    EveryRecord,
+    /// Flush after every `n` records.
+    Every(usize),
+    /// Only flush when [`NdjsonSink::flush`] is called, or on drop.
+    Manual,
+}
+
+/// Writes each reported error as a line of JSON, in the representation of
+/// [`SerializableError`].
+///
+/// Each line is written with a single call to [`Write::write_all`], so lines
+/// aren't interleaved when appending to a shared file.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{log::NdjsonSink, IoErrorExt as _};
+///
+/// let mut sink = NdjsonSink::new(Vec::new());
+/// sink.report(&io::Error::not_found("no such table").context("loading schema"))?;
+/// sink.report(&io::Error::from(io::ErrorKind::TimedOut))?;
+///
+/// let log = String::from_utf8(sink.into_inner()?).unwrap();
+/// let mut lines = log.lines();
+/// assert_eq!(
+///     lines.next().unwrap(),
+///     r#"{"version":1,"kind":"NotFound","message":"loading schema","causes":["no such table"],"os_code":null}"#,
+/// );
+/// assert_eq!(
+///     lines.next().unwrap(),
+///     r#"{"version":1,"kind":"TimedOut","message":"timed out","causes":[],"os_code":null}"#,
+/// );
+/// # Ok::<_, io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct NdjsonSink<W: Write> {
+    inner: Option<W>,
+    policy: FlushPolicy,
+    unflushed: usize,
+    line: Vec<u8>,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    /// Write records to `inner`, flushing after each one.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: Some(inner),
+            policy: FlushPolicy::default(),
+            unflushed: 0,
+            line: Vec::new(),
+        }
+    }
+    /// Choose when to flush the writer.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Write `e` as a single line.
+    pub fn report(&mut self, e: &io::Error) -> io::Result<()> {
+        self.report_serializable(&SerializableError::from(e))
+    }
+    /// Write an already-serializable error as a single line.
+    pub fn report_serializable(&mut self, e: &SerializableError) -> io::Result<()> {
+        self.line.clear();
+        serde_json::to_writer(&mut self.line, e)
+            .map_err(|e| io::Error::invalid_data(e).context("serializing error record"))?;
+        self.line.push(b'\n');
+        self.inner
+            .as_mut()
+            .expect("only taken by into_inner")
+            .write_all(&self.line)?;
+        self.unflushed += 1;
+        let flush = match self.policy {
+            FlushPolicy::EveryRecord => true,
+            FlushPolicy::Every(n) => self.unflushed >= n,
+            FlushPolicy::Manual => false,
+        };
+        match flush {
+            true => self.flush(),
+            false => Ok(()),
+        }
+    }
+    /// Flush the writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.unflushed = 0;
+        self.get_mut().flush()
+    }
+    /// Get a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("only taken by into_inner")
+    }
+    /// Get a mutable reference to the writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("only taken by into_inner")
+    }
+    /// Flush and unwrap the writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner.take().expect("only taken by into_inner"))
+    }
+}
+
+impl<W: Write> Drop for NdjsonSink<W> {
+    fn drop(&mut self) {
+        if let Some(inner) = &mut self.inner {
+            let _ = inner.flush();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/// Create a closure which attaches a formatted message to an [`io::Error`](std::io::Error),
+/// also recording each named placeholder as a structured [field](crate::fields()).
+///
+/// Every placeholder must be named, and every name must be used.
+/// The message is only formatted if there is an error.
+///
+/// ```
+/// use std::{fs, io, path::Path};
+/// use io_extra::ctxt;
+///
+/// fn copy(src: &Path, dst: &Path) -> io::Result<u64> {
+///     let (src, dst) = (src.display(), dst.display());
+///     fs::copy(src.to_string(), dst.to_string()).map_err(ctxt!("copying {src} → {dst}", src, dst))
+/// }
+/// ```
+#[macro_export]
+macro_rules! ctxt {
+    ($fmt:literal $(, $field:ident)* $(,)?) => {
+        |e: ::std::io::Error| -> ::std::io::Error {
+            $crate::__private::context_fields(
+                e,
+                ::std::format_args!($fmt, $($field = $field),*),
+                [$((::std::stringify!($field), &$field as &dyn ::std::fmt::Display)),*],
+            )
+        }
+    };
+}
+
+/// Create an [`io::Error`](std::io::Error) from a kind and a message known at
+/// compile time, without formatting or copying the message.
+///
+/// This is shorthand for converting a [`StaticError`](crate::StaticError).
+///
+/// ```
+/// use std::io;
+/// use io_extra::const_io_error;
+///
+/// fn validate(len: usize) -> io::Result<()> {
+///     match len <= 4096 {
+///         true => Ok(()),
+///         false => Err(const_io_error!(io::ErrorKind::InvalidInput, "record is too long")),
+///     }
+/// }
+/// assert_eq!(validate(5000).unwrap_err().to_string(), "record is too long");
+/// ```
+#[macro_export]
+macro_rules! const_io_error {
+    ($kind:expr, $message:expr $(,)?) => {
+        ::std::io::Error::from({
+            const ERROR: $crate::StaticError = $crate::StaticError::new($kind, $message);
+            ERROR
+        })
+    };
+}
+
+/// Return early with an [`io::Error`](std::io::Error) of the named
+/// [`ErrorKind`](std::io::ErrorKind) and a formatted message.
+///
+/// ```
+/// use std::io;
+/// use io_extra::bail;
+///
+/// fn parse(magic: u32) -> io::Result<()> {
+///     if magic != 0xcafebabe {
+///         bail!(InvalidData, "bad header {:x}", magic);
+///     }
+///     Ok(())
+/// }
+/// let e = parse(0xdeadbeef).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(e.to_string(), "bad header deadbeef");
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ($kind:ident, $($fmt:tt)+) => {
+        return ::std::result::Result::Err(::std::io::Error::new(
+            ::std::io::ErrorKind::$kind,
+            ::std::format!($($fmt)+),
+        ))
+    };
+}
+
+/// Return early with an [`io::Error`](std::io::Error), as in [`bail!`], unless
+/// a condition holds.
+///
+/// ```
+/// use std::{collections::HashMap, io};
+/// use io_extra::ensure;
+///
+/// fn lookup(entries: &HashMap<&str, u64>, name: &str) -> io::Result<u64> {
+///     ensure!(entries.contains_key(name), NotFound, "missing entry {name}");
+///     Ok(entries[name])
+/// }
+/// let e = lookup(&HashMap::new(), "etc").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert_eq!(e.to_string(), "missing entry etc");
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $kind:ident, $($fmt:tt)+) => {
+        if !$cond {
+            $crate::bail!($kind, $($fmt)+)
+        }
+    };
+}
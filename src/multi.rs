@@ -0,0 +1,184 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+    slice, vec,
+};
+
+/// Split `results` into successes and a consolidated report of failures,
+/// for batch operations that shouldn't abort on the first error.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{partition_io, IoErrorExt as _};
+///
+/// let (ok, errors) = partition_io([
+///     Ok(1),
+///     Err(io::Error::not_found("no file 2")),
+///     Ok(3),
+/// ]);
+/// assert_eq!(ok, [1, 3]);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors.to_string(), "1 error: no file 2");
+/// ```
+pub fn partition_io<T>(results: impl IntoIterator<Item = io::Result<T>>) -> (Vec<T>, MultiError) {
+    let mut ok = vec![];
+    let mut errors = MultiError::new();
+    for result in results {
+        match result {
+            Ok(it) => ok.push(it),
+            Err(e) => errors.push(e),
+        }
+    }
+    (ok, errors)
+}
+
+/// A collection of [`io::Error`]s, reported together.
+///
+/// Converting to an [`io::Error`] takes the [`kind`](Self::kind) of the most
+/// severe error.
+#[derive(Debug, Default)]
+pub struct MultiError {
+    errors: Vec<io::Error>,
+}
+
+impl MultiError {
+    /// Create an empty collection.
+    pub const fn new() -> Self {
+        Self { errors: Vec::new() }
+    }
+    /// Add an error.
+    pub fn push(&mut self, e: io::Error) {
+        self.errors.push(e)
+    }
+    /// The number of errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no errors.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Iterate over the errors.
+    pub fn iter(&self) -> slice::Iter<'_, io::Error> {
+        self.errors.iter()
+    }
+    /// Return `Ok(())` if there are no errors, or convert to an [`io::Error`].
+    pub fn into_result(self) -> io::Result<()> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self.into()),
+        }
+    }
+    /// The most severe error, by [`kind_priority`](crate::kind_priority), or the
+    /// first of equally severe ones.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, MultiError};
+    ///
+    /// let errors = MultiError::from(vec![
+    ///     io::Error::timed_out("replica 1 is slow"),
+    ///     io::Error::permission_denied("replica 2 rejected our token"),
+    ///     io::Error::timed_out("replica 3 is slow"),
+    /// ]);
+    /// assert_eq!(errors.representative().unwrap().to_string(), "replica 2 rejected our token");
+    /// assert_eq!(errors.kind(), io::ErrorKind::PermissionDenied);
+    /// ```
+    pub fn representative(&self) -> Option<&io::Error> {
+        self.errors
+            .iter()
+            .rev()
+            .max_by_key(|it| crate::kind_priority(it.kind()))
+    }
+    /// The kind of the [representative](Self::representative) error, or
+    /// [`ErrorKind::Other`] if there are none.
+    pub fn kind(&self) -> ErrorKind {
+        self.representative()
+            .map(io::Error::kind)
+            .unwrap_or(ErrorKind::Other)
+    }
+}
+
+impl fmt::Display for MultiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            1 => f.write_str("1 error")?,
+            n => write!(f, "{} errors", n)?,
+        }
+        for (ix, e) in self.errors.iter().enumerate() {
+            f.write_str(if ix == 0 { ": " } else { "; " })?;
+            match f.alternate() {
+                true => write!(f, "{:#}", e)?,
+                false => write!(f, "{}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for MultiError {}
+
+impl From<MultiError> for io::Error {
+    fn from(value: MultiError) -> Self {
+        io::Error::new(value.kind(), value)
+    }
+}
+
+impl From<Vec<io::Error>> for MultiError {
+    fn from(errors: Vec<io::Error>) -> Self {
+        Self { errors }
+    }
+}
+
+impl FromIterator<io::Error> for MultiError {
+    fn from_iter<T: IntoIterator<Item = io::Error>>(iter: T) -> Self {
+        Self {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<io::Error> for MultiError {
+    fn extend<T: IntoIterator<Item = io::Error>>(&mut self, iter: T) {
+        self.errors.extend(iter)
+    }
+}
+
+impl IntoIterator for MultiError {
+    type Item = io::Error;
+    type IntoIter = vec::IntoIter<io::Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MultiError {
+    type Item = &'a io::Error;
+    type IntoIter = slice::Iter<'a, io::Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
+/// See [`IoErrorExt::or_fallback`](crate::IoErrorExt::or_fallback).
+#[derive(Debug)]
+pub(crate) struct Fallback {
+    pub(crate) primary: io::Error,
+    pub(crate) fallback: io::Error,
+}
+
+impl fmt::Display for Fallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Both chains are shown in full, in place of a single source.
+        write!(
+            f,
+            "primary attempt failed: {:#}; fallback also failed: {:#}",
+            self.primary, self.fallback
+        )
+    }
+}
+
+impl Error for Fallback {}
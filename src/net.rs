@@ -0,0 +1,286 @@
+//! Helpers for network code.
+
+use std::{
+    fmt,
+    io::{self, ErrorKind::Interrupted, Read, Write},
+    net::{self, TcpStream, ToSocketAddrs as _},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{context, context::context_fields, IoErrorExt as _};
+
+/// Streams which can be shut down, as needed by [`Duplex`].
+pub trait Shutdown {
+    /// Shut down the read half, write half, or both halves of the stream.
+    fn shutdown(&self, how: net::Shutdown) -> io::Result<()>;
+}
+
+impl Shutdown for TcpStream {
+    fn shutdown(&self, how: net::Shutdown) -> io::Result<()> {
+        TcpStream::shutdown(self, how)
+    }
+}
+
+#[cfg(unix)]
+impl Shutdown for std::os::unix::net::UnixStream {
+    fn shutdown(&self, how: net::Shutdown) -> io::Result<()> {
+        std::os::unix::net::UnixStream::shutdown(self, how)
+    }
+}
+
+/// The number of bytes moved in each direction by [`Duplex::run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Transferred {
+    /// Bytes copied from the first endpoint to the second.
+    pub a_to_b: u64,
+    /// Bytes copied from the second endpoint to the first.
+    pub b_to_a: u64,
+}
+
+/// Bidirectional copying between two named endpoints, for proxies and tunnels.
+///
+/// Each direction is copied on its own thread.
+/// When a direction reaches the end of its stream, the write half of its
+/// destination is shut down, so the peer sees a clean close.
+/// [Benign disconnections](crate::IoErrorExt::is_benign_disconnect) are treated
+/// as the end of the stream.
+///
+/// Any other failure shuts down both endpoints, and is reported with the
+/// direction and endpoint which failed, and the bytes moved in each direction.
+///
+/// ```
+/// # #[cfg(unix)] {
+/// use std::{io::{Read as _, Write as _}, net::Shutdown, os::unix::net::UnixStream, thread};
+/// use io_extra::net::{Duplex, Transferred};
+///
+/// let (client, mut client_peer) = UnixStream::pair().unwrap();
+/// let (upstream, mut upstream_peer) = UnixStream::pair().unwrap();
+/// let proxy = thread::spawn(move || Duplex::new("client", "upstream").run(&client, &upstream));
+///
+/// client_peer.write_all(b"ping").unwrap();
+/// client_peer.shutdown(Shutdown::Write).unwrap();
+/// let mut buf = String::new();
+/// upstream_peer.read_to_string(&mut buf).unwrap();
+/// assert_eq!(buf, "ping");
+///
+/// upstream_peer.write_all(b"pong!").unwrap();
+/// upstream_peer.shutdown(Shutdown::Write).unwrap();
+/// buf.clear();
+/// client_peer.read_to_string(&mut buf).unwrap();
+/// assert_eq!(buf, "pong!");
+///
+/// assert_eq!(proxy.join().unwrap().unwrap(), Transferred { a_to_b: 4, b_to_a: 5 });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Duplex {
+    a: String,
+    b: String,
+}
+
+/// A failed direction: which endpoint failed, how, and after how many bytes.
+struct Failure<'a> {
+    endpoint: &'a str,
+    reading: bool,
+    error: io::Error,
+    moved: u64,
+}
+
+impl Duplex {
+    /// Name the two endpoints, for error messages.
+    pub fn new(a: impl Into<String>, b: impl Into<String>) -> Self {
+        Self {
+            a: a.into(),
+            b: b.into(),
+        }
+    }
+
+    /// Copy between `a` and `b` until both directions are finished.
+    pub fn run<A, B>(&self, a: &A, b: &B) -> io::Result<Transferred>
+    where
+        A: Shutdown + Sync,
+        B: Shutdown + Sync,
+        for<'x> &'x A: Read + Write,
+        for<'x> &'x B: Read + Write,
+    {
+        let abort = || {
+            let _ = a.shutdown(net::Shutdown::Both);
+            let _ = b.shutdown(net::Shutdown::Both);
+        };
+        let (a_to_b, b_to_a) = thread::scope(|scope| {
+            let a_to_b = scope.spawn(|| {
+                let res = copy(a, b, &self.a, &self.b);
+                match res {
+                    Ok(_) => {
+                        let _ = b.shutdown(net::Shutdown::Write);
+                    }
+                    Err(_) => abort(),
+                }
+                res
+            });
+            let b_to_a = copy(b, a, &self.b, &self.a);
+            match b_to_a {
+                Ok(_) => {
+                    let _ = a.shutdown(net::Shutdown::Write);
+                }
+                Err(_) => abort(),
+            }
+            let a_to_b = a_to_b.join().expect("copying thread panicked");
+            (a_to_b, b_to_a)
+        });
+        let moved = |it: &Result<u64, Failure>| match it {
+            Ok(n) => *n,
+            Err(failure) => failure.moved,
+        };
+        let transferred = Transferred {
+            a_to_b: moved(&a_to_b),
+            b_to_a: moved(&b_to_a),
+        };
+        let describe = |failure: Failure, (from, to): (&str, &str), other: u64| {
+            context(
+                failure.error,
+                format_args!(
+                    "copying {}→{}: {} {} failed after {} bytes ({}→{} moved {} bytes)",
+                    from,
+                    to,
+                    if failure.reading {
+                        "reading from"
+                    } else {
+                        "writing to"
+                    },
+                    failure.endpoint,
+                    failure.moved,
+                    to,
+                    from,
+                    other,
+                ),
+            )
+        };
+        match (a_to_b, b_to_a) {
+            (Ok(_), Ok(_)) => Ok(transferred),
+            (Err(failure), Ok(_)) => Err(describe(failure, (&self.a, &self.b), transferred.b_to_a)),
+            (Ok(_), Err(failure)) => Err(describe(failure, (&self.b, &self.a), transferred.a_to_b)),
+            (Err(first), Err(second)) => Err(context::suppress(
+                describe(first, (&self.a, &self.b), transferred.b_to_a),
+                describe(second, (&self.b, &self.a), transferred.a_to_b),
+            )),
+        }
+    }
+}
+
+fn copy<'a, R, W>(
+    mut from: R,
+    mut to: W,
+    from_name: &'a str,
+    to_name: &'a str,
+) -> Result<u64, Failure<'a>>
+where
+    R: Read,
+    W: Write,
+{
+    let mut buf = [0; 8 * 1024];
+    let mut moved = 0;
+    loop {
+        let n = match from.read(&mut buf) {
+            Ok(0) => return Ok(moved),
+            Ok(n) => n,
+            Err(e) if e.kind() == Interrupted => continue,
+            Err(e) if e.is_benign_disconnect(true) => return Ok(moved),
+            Err(error) => {
+                return Err(Failure {
+                    endpoint: from_name,
+                    reading: true,
+                    error,
+                    moved,
+                })
+            }
+        };
+        match to.write_all(&buf[..n]) {
+            Ok(()) => moved += n as u64,
+            Err(e) if e.is_benign_disconnect(true) => return Ok(moved),
+            Err(error) => {
+                return Err(Failure {
+                    endpoint: to_name,
+                    reading: false,
+                    error,
+                    moved,
+                })
+            }
+        }
+    }
+}
+
+/// Resolve `host` and connect to it on `port`, giving up after `timeout`.
+///
+/// Resolution and connection are timed separately, so that errors say which
+/// phase failed, and how long it took, in the message and as `phase`
+/// (`resolve` or `connect`) and `elapsed_ms` [fields](crate::fields()).
+/// Each resolved address is tried in turn, with the time remaining.
+///
+/// The system resolver cannot be cancelled, so resolution runs on a helper
+/// thread which is left to finish in the background if it times out.
+///
+/// ```
+/// use std::{io, net::TcpListener, time::Duration};
+/// use io_extra::{fields, net::connect_host};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let port = listener.local_addr()?.port();
+/// connect_host("127.0.0.1", port, Duration::from_secs(5))?;
+///
+/// drop(listener);
+/// let e = connect_host("127.0.0.1", port, Duration::from_secs(5)).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+/// assert!(e.to_string().starts_with(&format!("connecting to 127.0.0.1:{} after", port)));
+/// assert!(fields(&e).any(|it| it == ("phase", "connect")));
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn connect_host(host: &str, port: u16, timeout: Duration) -> io::Result<TcpStream> {
+    let start = Instant::now();
+    let fail = |e, phase: &str, msg: fmt::Arguments| {
+        let elapsed = start.elapsed();
+        context_fields(
+            e,
+            format_args!("{} after {:.1}s", msg, elapsed.as_secs_f64()),
+            [
+                ("phase", phase.to_string()),
+                ("elapsed_ms", elapsed.as_millis().to_string()),
+            ],
+        )
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let owned = String::from(host);
+    thread::spawn(move || {
+        let _ = tx.send((owned.as_str(), port).to_socket_addrs().map(Vec::from_iter));
+    });
+    let addrs = match rx.recv_timeout(timeout) {
+        Ok(Ok(addrs)) if addrs.is_empty() => Err(io::Error::not_found("no addresses found")),
+        Ok(res) => res,
+        Err(_) => Err(io::Error::timed_out("resolution did not complete")),
+    }
+    .map_err(|e| fail(e, "resolve", format_args!("resolving {}", host)))?;
+
+    let mut last = None;
+    for addr in addrs {
+        let remaining = match timeout.checked_sub(start.elapsed()) {
+            Some(it) if !it.is_zero() => it,
+            _ => {
+                last = Some(io::Error::timed_out("no time left to connect"));
+                break;
+            }
+        };
+        match TcpStream::connect_timeout(&addr, remaining) {
+            Ok(it) => return Ok(it),
+            Err(e) => last = Some(e),
+        }
+    }
+    Err(fail(
+        last.expect("there is at least one address"),
+        "connect",
+        format_args!("connecting to {}:{}", host, port),
+    ))
+}
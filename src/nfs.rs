@@ -0,0 +1,149 @@
+//! Mapping between [`io::Error`]s and NFS status codes (`nfsstat3`/`nfsstat4`).
+//!
+//! ```
+//! use std::io;
+//! use io_extra::{nfs, IoErrorExt as _};
+//!
+//! let e = nfs::error(nfs::NFSERR_STALE).context("reading block 7");
+//! assert_eq!(format!("{:#}", e), "reading block 7: NFSERR_STALE (70)");
+//! assert_eq!(nfs::status_of(&e), nfs::NFSERR_STALE);
+//!
+//! assert_eq!(nfs::status_of(&io::Error::permission_denied("no")), nfs::NFSERR_ACCES);
+//!
+//! // Filesystem conditions map to their own kinds, in both directions.
+//! for status in [
+//!     nfs::NFSERR_NOTDIR,
+//!     nfs::NFSERR_ISDIR,
+//!     nfs::NFSERR_NOTEMPTY,
+//!     nfs::NFSERR_NOSPC,
+//!     nfs::NFSERR_DQUOT,
+//!     nfs::NFSERR_XDEV,
+//! ] {
+//!     assert_eq!(nfs::status(nfs::kind(status).unwrap()), status);
+//! }
+//! assert_eq!(nfs::error(nfs::NFSERR_NOTEMPTY).kind(), io::ErrorKind::DirectoryNotEmpty);
+//! ```
+
+use std::io::{self, ErrorKind::*};
+
+use crate::kind::{newer_kinds, statuses};
+
+newer_kinds! {
+    #[cfg(io_error_crosses_devices)] CROSSES_DEVICES = CrossesDevices else Other;
+    #[cfg(io_error_more)] NOT_A_DIRECTORY = NotADirectory else Other;
+    #[cfg(io_error_more)] IS_A_DIRECTORY = IsADirectory else Other;
+    #[cfg(io_error_more)] FILE_TOO_LARGE = FileTooLarge else Other;
+    #[cfg(io_error_more)] STORAGE_FULL = StorageFull else Other;
+    #[cfg(io_error_more)] READ_ONLY_FILESYSTEM = ReadOnlyFilesystem else PermissionDenied;
+    #[cfg(io_error_more)] TOO_MANY_LINKS = TooManyLinks else Other;
+    #[cfg(io_error_invalid_filename)] INVALID_FILENAME = InvalidFilename else InvalidInput;
+    #[cfg(io_error_more)] DIRECTORY_NOT_EMPTY = DirectoryNotEmpty else Other;
+    #[cfg(io_error_quota_exceeded)] QUOTA_EXCEEDED = QuotaExceeded else Other;
+    #[cfg(io_error_more)] STALE_NETWORK_FILE_HANDLE = StaleNetworkFileHandle else Other;
+}
+
+statuses! {
+    /// An NFS status code, as the payload of an [`io::Error`].
+    pub struct NfsStatus;
+
+    /// The call completed successfully.
+    NFS_OK = 0;
+
+    display = "{} ({})", "{=str} ({=u32})";
+    unknown = "NFS status {}", "NFS status {=u32}";
+
+    /// Not owner.
+    NFSERR_PERM = 1 => PermissionDenied;
+    /// No such file or directory.
+    NFSERR_NOENT = 2 => NotFound;
+    /// A hard error occurred.
+    NFSERR_IO = 5 => Other;
+    /// No such device or address.
+    NFSERR_NXIO = 6 => NotFound;
+    /// Permission denied.
+    NFSERR_ACCES = 13 => PermissionDenied;
+    /// The file already exists.
+    NFSERR_EXIST = 17 => AlreadyExists;
+    /// Attempt to do a cross-device hard link.
+    NFSERR_XDEV = 18 => CROSSES_DEVICES;
+    /// No such device.
+    NFSERR_NODEV = 19 => NotFound;
+    /// Not a directory.
+    NFSERR_NOTDIR = 20 => NOT_A_DIRECTORY;
+    /// Is a directory.
+    NFSERR_ISDIR = 21 => IS_A_DIRECTORY;
+    /// An invalid or unsupported argument.
+    NFSERR_INVAL = 22 => InvalidInput;
+    /// The file is too large.
+    NFSERR_FBIG = 27 => FILE_TOO_LARGE;
+    /// No space left on device.
+    NFSERR_NOSPC = 28 => STORAGE_FULL;
+    /// Read-only file system.
+    NFSERR_ROFS = 30 => READ_ONLY_FILESYSTEM;
+    /// Too many hard links.
+    NFSERR_MLINK = 31 => TOO_MANY_LINKS;
+    /// The filename is too long.
+    NFSERR_NAMETOOLONG = 63 => INVALID_FILENAME;
+    /// The directory is not empty.
+    NFSERR_NOTEMPTY = 66 => DIRECTORY_NOT_EMPTY;
+    /// The quota was exceeded.
+    NFSERR_DQUOT = 69 => QUOTA_EXCEEDED;
+    /// The file handle is no longer valid.
+    NFSERR_STALE = 70 => STALE_NETWORK_FILE_HANDLE;
+    /// Too many levels of remote in path.
+    NFSERR_REMOTE = 71 => Other;
+    /// Illegal NFS file handle.
+    NFSERR_BADHANDLE = 10001 => InvalidInput;
+    /// Update synchronization mismatch.
+    NFSERR_NOT_SYNC = 10002 => Other;
+    /// A directory cookie is stale.
+    NFSERR_BAD_COOKIE = 10003 => InvalidInput;
+    /// The operation is not supported.
+    NFSERR_NOTSUPP = 10004 => Unsupported;
+    /// The buffer or request is too small.
+    NFSERR_TOOSMALL = 10005 => InvalidInput;
+    /// An error occurred on the server which does not map to any legal status.
+    NFSERR_SERVERFAULT = 10006 => Other;
+    /// The type of the object is not supported.
+    NFSERR_BADTYPE = 10007 => Unsupported;
+    /// The server initiated the request, but was not able to complete it in a
+    /// timely fashion. The client should retry later.
+    NFSERR_JUKEBOX = 10008 => WouldBlock;
+}
+
+/// The NFS status to report for errors of `kind`.
+///
+/// Kinds without a more specific code are reported as [`NFSERR_IO`].
+pub fn status(kind: io::ErrorKind) -> u32 {
+    match kind {
+        NotFound => NFSERR_NOENT,
+        PermissionDenied => NFSERR_ACCES,
+        AlreadyExists => NFSERR_EXIST,
+        InvalidInput => NFSERR_INVAL,
+        Unsupported => NFSERR_NOTSUPP,
+        WouldBlock => NFSERR_JUKEBOX,
+        #[cfg(io_error_crosses_devices)]
+        CrossesDevices => NFSERR_XDEV,
+        #[cfg(io_error_more)]
+        NotADirectory => NFSERR_NOTDIR,
+        #[cfg(io_error_more)]
+        IsADirectory => NFSERR_ISDIR,
+        #[cfg(io_error_more)]
+        FileTooLarge => NFSERR_FBIG,
+        #[cfg(io_error_more)]
+        StorageFull => NFSERR_NOSPC,
+        #[cfg(io_error_more)]
+        ReadOnlyFilesystem => NFSERR_ROFS,
+        #[cfg(io_error_more)]
+        TooManyLinks => NFSERR_MLINK,
+        #[cfg(io_error_invalid_filename)]
+        InvalidFilename => NFSERR_NAMETOOLONG,
+        #[cfg(io_error_more)]
+        DirectoryNotEmpty => NFSERR_NOTEMPTY,
+        #[cfg(io_error_quota_exceeded)]
+        QuotaExceeded => NFSERR_DQUOT,
+        #[cfg(io_error_more)]
+        StaleNetworkFileHandle => NFSERR_STALE,
+        _ => NFSERR_IO,
+    }
+}
@@ -0,0 +1,83 @@
+//! Funnelling file watcher failures from [`notify`] into [`io::Error`]s,
+//! for sync daemons which handle every failure in one place.
+//!
+//! ```
+//! use std::io;
+//! use io_extra::{notify::check_event, path};
+//! use notify::{event::Flag, Event, EventKind};
+//!
+//! let event = Event::new(EventKind::Any).add_path("/srv/share".into());
+//! assert!(check_event(Ok(event.clone())).is_ok());
+//!
+//! let e = check_event(Ok(event.set_flag(Flag::Rescan))).unwrap_err();
+//! assert_eq!(format!("{:#}", e), "/srv/share: watch queue overflowed; events lost");
+//! assert_eq!(path(&e).unwrap().to_str(), Some("/srv/share"));
+//! ```
+
+use std::io::{self, ErrorKind::*};
+
+use ::notify::{Error, ErrorKind, Event};
+
+use crate::IoErrorExt as _;
+
+/// Convert a watcher error, recording its first path with
+/// [`with_path`](crate::IoErrorExt::with_path).
+///
+/// Hitting the limit on watches is [`OutOfMemory`], as the kernel has run out of
+/// a resource.
+/// [`PathNotFound`](ErrorKind::PathNotFound) and
+/// [`WatchNotFound`](ErrorKind::WatchNotFound) are [`NotFound`], and invalid
+/// configuration is [`InvalidInput`].
+/// IO errors are returned as-is.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{notify::from_notify, path};
+///
+/// let e = from_notify(notify::Error::path_not_found().add_path("/srv/share".into()));
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert_eq!(format!("{:#}", e), "/srv/share: No path was found.");
+///
+/// let e = from_notify(notify::Error::new(notify::ErrorKind::MaxFilesWatch));
+/// assert_eq!(e.kind(), io::ErrorKind::OutOfMemory);
+/// ```
+pub fn from_notify(e: Error) -> io::Error {
+    let path = e.paths.into_iter().next();
+    let e = match e.kind {
+        ErrorKind::Io(it) => it,
+        kind => {
+            let io_kind = match &kind {
+                ErrorKind::PathNotFound | ErrorKind::WatchNotFound => NotFound,
+                ErrorKind::InvalidConfig(_) => InvalidInput,
+                ErrorKind::MaxFilesWatch => OutOfMemory,
+                _ => Other,
+            };
+            // Without the paths, which are recorded separately.
+            io::Error::new(io_kind, Error::new(kind))
+        }
+    };
+    match path {
+        Some(path) => e.with_path(path),
+        None => e,
+    }
+}
+
+/// Check a result from a watcher's event handler, converting errors with
+/// [`from_notify`].
+///
+/// Events flagged as needing a [rescan](Event::need_rescan) mean that the
+/// watcher's queue overflowed, so are converted to an [`Other`] error with the
+/// event's first path.
+pub fn check_event(res: ::notify::Result<Event>) -> io::Result<Event> {
+    match res {
+        Ok(event) if event.need_rescan() => {
+            let e = io::Error::other("watch queue overflowed; events lost");
+            Err(match event.paths.into_iter().next() {
+                Some(path) => e.with_path(path),
+                None => e,
+            })
+        }
+        Ok(event) => Ok(event),
+        Err(e) => Err(from_notify(e)),
+    }
+}
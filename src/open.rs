@@ -0,0 +1,202 @@
+use std::{
+    fmt,
+    io::{self, ErrorKind},
+    net::TcpStream,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+type OpenRead = dyn Fn(&str) -> io::Result<Box<dyn io::Read + Send>> + Send + Sync;
+type OpenWrite = dyn Fn(&str) -> io::Result<Box<dyn io::Write + Send>> + Send + Sync;
+
+/// How to open locations of the form `scheme://rest`, for [`open_source`] and
+/// [`open_sink`].
+///
+/// Register schemes with [`register_scheme`].
+/// Openers are passed the `rest` of the location, and should attach their own
+/// context to any error.
+/// They are called without any lock held, so may themselves open locations or
+/// register schemes.
+#[derive(Default)]
+pub struct Scheme {
+    read: Option<Arc<OpenRead>>,
+    write: Option<Arc<OpenWrite>>,
+}
+
+impl Scheme {
+    /// A scheme which can't be opened for reading or writing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Open locations for reading with `f`.
+    pub fn read<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> io::Result<Box<dyn io::Read + Send>> + Send + Sync + 'static,
+    {
+        self.read = Some(Arc::new(f));
+        self
+    }
+    /// Open locations for writing with `f`.
+    pub fn write<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> io::Result<Box<dyn io::Write + Send>> + Send + Sync + 'static,
+    {
+        self.write = Some(Arc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheme")
+            .field("read", &self.read.is_some())
+            .field("write", &self.write.is_some())
+            .finish()
+    }
+}
+
+/// How long the built-in `tcp://` scheme waits to resolve and connect.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static SCHEMES: RwLock<Vec<(String, Scheme)>> = RwLock::new(Vec::new());
+
+/// Teach [`open_source`] and [`open_sink`] to open `name://` locations,
+/// process-wide, replacing any previous (or built-in) scheme of that name.
+///
+/// ```
+/// use std::io::{self, Read as _};
+/// use io_extra::{open_source, register_scheme, Scheme};
+///
+/// register_scheme(
+///     "echo",
+///     Scheme::new().read(|rest| Ok(Box::new(io::Cursor::new(rest.to_owned())))),
+/// );
+///
+/// let mut s = String::new();
+/// open_source("echo://hello")?.read_to_string(&mut s)?;
+/// assert_eq!(s, "hello");
+///
+/// let e = io_extra::open_sink("echo://hello").err().unwrap();
+/// assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+/// assert_eq!(e.to_string(), "echo://hello can't be opened for writing");
+///
+/// // Openers may open other locations.
+/// register_scheme(
+///     "shout",
+///     Scheme::new().read(|rest| open_source(&format!("echo://{}", rest.to_uppercase()))),
+/// );
+/// let mut s = String::new();
+/// open_source("shout://hello")?.read_to_string(&mut s)?;
+/// assert_eq!(s, "HELLO");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn register_scheme(name: impl Into<String>, scheme: Scheme) {
+    let name = name.into();
+    let mut schemes = SCHEMES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    schemes.retain(|(it, _)| *it != name);
+    schemes.push((name, scheme))
+}
+
+/// Open `location` for reading, as given on a command line.
+///
+/// - `-` is standard input.
+/// - `file://path`, or a plain path, is a file, opened with [`fs::open`](crate::fs::open).
+/// - `tcp://host:port` is a TCP connection, made with
+///   [`net::connect_host`](crate::net::connect_host), giving up after 30 seconds.
+/// - Other `scheme://` locations are handled by [registered](register_scheme)
+///   schemes, or are [`InvalidInput`](ErrorKind::InvalidInput) errors.
+///
+/// ```
+/// use std::io;
+/// use io_extra::open_source;
+///
+/// let e = open_source("file:///no/such/input.csv").err().unwrap();
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert!(format!("{:#}", e).starts_with("opening /no/such/input.csv: "));
+///
+/// let e = open_source("gopher://example.com").err().unwrap();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+/// assert_eq!(e.to_string(), "unknown scheme `gopher` in gopher://example.com");
+///
+/// let e = open_source("tcp://localhost").err().unwrap();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+/// ```
+pub fn open_source(location: &str) -> io::Result<Box<dyn io::Read + Send>> {
+    open(
+        location,
+        "reading",
+        |scheme| scheme.read.clone(),
+        || Ok(Box::new(io::stdin())),
+        |path| Ok(Box::new(crate::fs::open(path)?)),
+        |stream| Box::new(stream),
+    )
+}
+
+/// Open `location` for writing, as given on a command line.
+///
+/// As in [`open_source`], except that `-` is standard output, and files are
+/// created with [`fs::create`](crate::fs::create).
+pub fn open_sink(location: &str) -> io::Result<Box<dyn io::Write + Send>> {
+    open(
+        location,
+        "writing",
+        |scheme| scheme.write.clone(),
+        || Ok(Box::new(io::stdout())),
+        |path| Ok(Box::new(crate::fs::create(path)?)),
+        |stream| Box::new(stream),
+    )
+}
+
+fn open<T: ?Sized>(
+    location: &str,
+    direction: &str,
+    opener: impl Fn(&Scheme) -> Option<Arc<dyn Fn(&str) -> io::Result<Box<T>> + Send + Sync>>,
+    std: impl FnOnce() -> io::Result<Box<T>>,
+    file: impl FnOnce(&str) -> io::Result<Box<T>>,
+    tcp: impl FnOnce(TcpStream) -> Box<T>,
+) -> io::Result<Box<T>> {
+    if location == "-" {
+        return std();
+    }
+    let Some((name, rest)) = location.split_once("://") else {
+        return file(location);
+    };
+    // Don't hold the lock while opening, in case the opener takes it too.
+    let registered = SCHEMES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find(|(it, _)| it == name)
+        .map(|(_, scheme)| opener(scheme));
+    if let Some(open) = registered {
+        return match open {
+            Some(open) => open(rest),
+            None => Err(io::Error::new(
+                ErrorKind::Unsupported,
+                format!("{} can't be opened for {}", location, direction),
+            )),
+        };
+    }
+    match name {
+        "file" => file(rest),
+        "tcp" => {
+            let Some((host, port)) = rest
+                .rsplit_once(':')
+                .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+            else {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("expected tcp://host:port, not {}", location),
+                ));
+            };
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            crate::net::connect_host(host, port, CONNECT_TIMEOUT).map(tcp)
+        }
+        _ => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("unknown scheme `{}` in {}", name, location),
+        )),
+    }
+}
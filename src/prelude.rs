@@ -0,0 +1,17 @@
+//! The extension traits of this crate, imported anonymously so that their
+//! methods are in scope without any names clashing.
+//!
+//! ```
+//! use std::io;
+//! use io_extra::prelude::*;
+//!
+//! let e = Err::<(), _>(io::Error::not_found("no such file"))
+//!     .context("reading config")
+//!     .unwrap_err();
+//! assert!(e.is_not_found());
+//! ```
+
+pub use crate::{
+    AsIoError as _, IoErrorExt as _, IoResultExt as _, OptionIoExt as _, ReadExt as _,
+    WriteExt as _,
+};
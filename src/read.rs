@@ -0,0 +1,282 @@
+use std::{
+    fmt,
+    io::{
+        self,
+        ErrorKind::{Interrupted, InvalidData, UnexpectedEof},
+    },
+};
+
+/// Check that `r` has been exhausted, as a parser should after decoding a
+/// complete document.
+///
+/// If there is trailing data, an [`InvalidData`] error describing (up to the first
+/// 16 bytes of) it is returned.
+///
+/// ```
+/// use io_extra::expect_eof;
+/// expect_eof(&b""[..]).unwrap();
+///
+/// let e = expect_eof(&b"\xde\xad"[..]).unwrap_err();
+/// assert_eq!(
+///     e.to_string(),
+///     "trailing data: 2+ bytes after end of message, starting with dead",
+/// );
+/// ```
+pub fn expect_eof(mut r: impl io::Read) -> io::Result<()> {
+    let mut buf = [0; 16];
+    loop {
+        match r.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!(
+                        "trailing data: {}+ bytes after end of message, starting with {}",
+                        n,
+                        Hex(&buf[..n])
+                    ),
+                ))
+            }
+            Err(e) if e.kind() == Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read all of `r` into a buffer which starts at `initial` bytes, and grows
+/// geometrically up to `max` bytes.
+///
+/// Allocation failures are returned as [`OutOfMemory`](io::ErrorKind::OutOfMemory) errors with the attempted
+/// size, rather than aborting the process, and streams longer than `max` are
+/// rejected as [`InvalidData`], making this suitable for untrusted input.
+///
+/// ```
+/// use std::io;
+/// use io_extra::read_growing;
+///
+/// assert_eq!(read_growing(&b"hello"[..], 2, 16).unwrap(), b"hello");
+/// assert_eq!(read_growing(&b"hello"[..], 2, 5).unwrap(), b"hello");
+///
+/// let e = read_growing(&b"hello"[..], 2, 4).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+///
+/// let e = read_growing(&b"hello"[..], usize::MAX, usize::MAX).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::OutOfMemory);
+/// ```
+pub fn read_growing(mut r: impl io::Read, initial: usize, max: usize) -> io::Result<Vec<u8>> {
+    // `buf[..filled]` has been read, and the rest of `buf` is zeroed, ready to
+    // read into, so each byte is only zeroed once.
+    let mut buf = Vec::new();
+    let mut filled = 0;
+    loop {
+        if filled == buf.len() {
+            if filled >= max {
+                return match r.read(&mut [0]) {
+                    Ok(0) => Ok(buf),
+                    Ok(_) => Err(io::Error::new(
+                        InvalidData,
+                        format!("stream is longer than the limit of {} bytes", max),
+                    )),
+                    Err(e) if e.kind() == Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+            }
+            let target = match filled {
+                0 => initial.max(1),
+                _ => filled.saturating_mul(2),
+            }
+            .min(max);
+            crate::alloc::reserve_exact_io(&mut buf, target - filled)?;
+            // Not the capacity, which may exceed `max`.
+            buf.resize(target, 0);
+        }
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => {
+                buf.truncate(filled);
+                return Ok(buf);
+            }
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read all of `r` as UTF-8, failing with [`InvalidData`] at the first invalid
+/// sequence.
+///
+/// Unlike [`Read::read_to_string`](io::Read::read_to_string), the error includes
+/// the byte offset of the sequence.
+///
+/// ```
+/// use std::io;
+/// use io_extra::read_to_string_strict;
+///
+/// assert_eq!(read_to_string_strict(&b"caf\xc3\xa9"[..])?, "café");
+///
+/// let e = read_to_string_strict(&b"caf\xe9 au lait"[..]).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(e.to_string(), "invalid UTF-8 at byte offset 3: e9");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn read_to_string_strict(mut r: impl io::Read) -> io::Result<String> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| {
+        let bytes = e.as_bytes();
+        let offset = e.utf8_error().valid_up_to();
+        let end = match e.utf8_error().error_len() {
+            Some(len) => offset + len,
+            // Truncated at the end of the stream.
+            None => bytes.len(),
+        };
+        io::Error::new(
+            InvalidData,
+            format!(
+                "invalid UTF-8 at byte offset {}: {}",
+                offset,
+                Hex(&bytes[offset..end])
+            ),
+        )
+    })
+}
+
+/// Read all of `r` as UTF-8, replacing invalid sequences with
+/// [`U+FFFD`](char::REPLACEMENT_CHARACTER).
+///
+/// Also returns whether any replacements were made.
+///
+/// ```
+/// use io_extra::read_to_string_lossy_io;
+///
+/// assert_eq!(read_to_string_lossy_io(&b"caf\xc3\xa9"[..])?, (String::from("café"), false));
+/// assert_eq!(read_to_string_lossy_io(&b"caf\xe9"[..])?, (String::from("caf\u{FFFD}"), true));
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn read_to_string_lossy_io(mut r: impl io::Read) -> io::Result<(String, bool)> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    match String::from_utf8(buf) {
+        Ok(it) => Ok((it, false)),
+        Err(e) => Ok((String::from_utf8_lossy(e.as_bytes()).into_owned(), true)),
+    }
+}
+
+/// Fill `buf` exactly from `r`, like [`read_exact`](io::Read::read_exact), but
+/// describing what was being read in any error.
+///
+/// If `r` ends early, the [`UnexpectedEof`] error states how many bytes were
+/// needed and received, rather than std's "failed to fill whole buffer".
+/// Other errors are given the context "reading `what`".
+///
+/// ```
+/// use std::io;
+/// use io_extra::{read_exact_ctx, test::ClosedReader};
+///
+/// let mut header = [0; 512];
+/// let e = read_exact_ctx(&mut &[0; 173][..], &mut header, "tar header").unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(e.to_string(), "needed 512 bytes for tar header, got 173");
+///
+/// let e = read_exact_ctx(ClosedReader::default(), &mut header, "tar header").unwrap_err();
+/// assert_eq!(format!("{:#}", e), "reading tar header: reader is closed");
+/// ```
+pub fn read_exact_ctx(
+    mut r: impl io::Read,
+    buf: &mut [u8],
+    what: impl fmt::Display,
+) -> io::Result<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    UnexpectedEof,
+                    format!("needed {} bytes for {}, got {}", buf.len(), what, filled),
+                ))
+            }
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == Interrupted => continue,
+            Err(e) => return Err(crate::context(e, format_args!("reading {}", what))),
+        }
+    }
+    Ok(())
+}
+
+/// Call [`fill_buf`](io::BufRead::fill_buf), retrying on [`Interrupted`], and
+/// attaching `msg` to any other error.
+///
+/// An empty buffer means that `r` is exhausted.
+///
+/// ```
+/// use std::io::{self, BufRead as _};
+/// use io_extra::{fill_buf_ctx, test::ClosedReader};
+///
+/// let mut r = &b"hello"[..];
+/// assert_eq!(fill_buf_ctx(&mut r, "reading greeting")?, b"hello");
+///
+/// let e = fill_buf_ctx(&mut io::BufReader::new(ClosedReader::default()), "reading greeting")
+///     .unwrap_err();
+/// assert_eq!(format!("{:#}", e), "reading greeting: reader is closed");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn fill_buf_ctx<R: io::BufRead + ?Sized>(
+    r: &mut R,
+    msg: impl fmt::Display,
+) -> io::Result<&[u8]> {
+    loop {
+        match r.fill_buf() {
+            Ok([]) => return Ok(&[]),
+            Ok(_) => break,
+            Err(e) if e.kind() == Interrupted => continue,
+            Err(e) => return Err(crate::context(e, msg)),
+        }
+    }
+    // The buffer is already full, so this won't read again.
+    r.fill_buf()
+}
+
+/// Call [`fill_buf`](io::BufRead::fill_buf), retrying on [`Interrupted`], and
+/// failing with [`UnexpectedEof`] if `r` is exhausted.
+///
+/// The returned buffer is never empty.
+///
+/// ```
+/// use std::io;
+/// use io_extra::fill_nonempty;
+///
+/// assert_eq!(fill_nonempty(&mut &b"hello"[..])?, b"hello");
+///
+/// let e = fill_nonempty(&mut &b""[..]).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn fill_nonempty<R: io::BufRead + ?Sized>(r: &mut R) -> io::Result<&[u8]> {
+    loop {
+        match r.fill_buf() {
+            Ok([]) => {
+                return Err(io::Error::new(
+                    UnexpectedEof,
+                    "stream ended while more data was expected",
+                ))
+            }
+            Ok(_) => break,
+            Err(e) if e.kind() == Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    // The buffer is already full, so this won't read again.
+    r.fill_buf()
+}
+
+/// Lowercase hex rendering of some bytes.
+pub(crate) struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?
+        }
+        Ok(())
+    }
+}
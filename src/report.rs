@@ -0,0 +1,294 @@
+use std::{error::Error as _, fmt, io, panic, sync::Arc, thread};
+
+use crate::{context::Chain, Sanitizer};
+
+/// Renders an [`io::Error`] with its full chain of causes.
+///
+/// This is a polyfill for the unstable `std::error::Report`, producing the same
+/// output, so projects can keep one rendering path across stable and nightly.
+/// With the `nightly` feature, it may be converted into the
+/// standard library type.
+///
+/// Like [`anyhow`](https://docs.rs/anyhow), the [`Debug`](fmt::Debug) rendering
+/// is always pretty, so returning a [`Report`] from `main` prints the whole
+/// chain:
+///
+/// ```no_run
+/// use std::fs;
+/// use io_extra::{IoResultExt as _, Report};
+///
+/// fn main() -> Result<(), Report> {
+///     let config = fs::read_to_string("config.toml").context("reading config")?;
+///     println!("{}", config);
+///     Ok(())
+/// }
+/// ```
+///
+/// Any [suggestions](crate::hints) and [help URL](crate::help_of) for the error
+/// are shown on final `hint:` and `help:` lines.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{IoErrorExt as _, Report};
+///
+/// let e = io::Error::not_found("no such file")
+///     .context("reading config")
+///     .context("starting up");
+/// assert_eq!(
+///     Report::new(e).to_string(),
+///     "starting up: reading config: no such file",
+/// );
+/// ```
+///
+/// In pretty mode, or with the alternate flag (`{:#}`), each cause is listed
+/// on its own line:
+///
+/// ```
+/// # use std::io;
+/// # use io_extra::{IoErrorExt as _, Report};
+/// # let e = io::Error::not_found("no such file")
+/// #     .context("reading config")
+/// #     .context("starting up");
+/// assert_eq!(
+///     Report::new(e).pretty(true).to_string(),
+///     "\
+/// starting up
+///
+/// Caused by:
+///    0: reading config
+///    1: no such file"
+/// );
+/// ```
+pub struct Report {
+    error: io::Error,
+    pretty: bool,
+    sanitizer: Option<Arc<dyn Sanitizer + Send + Sync>>,
+}
+
+impl Report {
+    /// Create a single-line report of `error`.
+    pub fn new(error: io::Error) -> Self {
+        Self {
+            error,
+            pretty: false,
+            sanitizer: None,
+        }
+    }
+    /// Render each cause on its own line.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+    /// Pass every frame, hint, and help line through `sanitizer` before it is
+    /// rendered.
+    ///
+    /// ```
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, Redact, Report};
+    ///
+    /// let e = io::Error::permission_denied("can't read /home/ferris/.ssh/id_ed25519")
+    ///     .context("loading keys for ferris");
+    /// let report = Report::new(e).sanitized(Redact::new().literal("ferris"));
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "loading keys for <redacted>: can't read /home/<redacted>/.ssh/id_ed25519",
+    /// );
+    /// assert!(!format!("{:?}", report).contains("ferris"));
+    /// ```
+    pub fn sanitized(mut self, sanitizer: impl Sanitizer + Send + Sync + 'static) -> Self {
+        self.sanitizer = Some(Arc::new(sanitizer));
+        self
+    }
+    /// Get a reference to the error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.error
+    }
+    /// Unwrap the error.
+    pub fn into_inner(self) -> io::Error {
+        self.error
+    }
+    /// Convert to the standard library's report, preserving the rendering options.
+    ///
+    /// The standard library's report can't be [sanitized](Self::sanitized), so
+    /// any sanitizer is dropped.
+    ///
+    /// ```
+    /// #![feature(error_reporter)]
+    /// use std::io;
+    /// use io_extra::{IoErrorExt as _, Report};
+    ///
+    /// let e = || io::Error::not_found("no such file").context("reading config");
+    /// assert_eq!(
+    ///     Report::new(e()).pretty(true).to_string(),
+    ///     Report::new(e()).pretty(true).into_std().to_string(),
+    /// );
+    /// ```
+    #[cfg(feature = "nightly")]
+    pub fn into_std(self) -> std::error::Report<io::Error> {
+        std::error::Report::new(self.error).pretty(self.pretty)
+    }
+}
+
+impl From<io::Error> for Report {
+    fn from(error: io::Error) -> Self {
+        Self::new(error)
+    }
+}
+
+impl From<Report> for io::Error {
+    fn from(report: Report) -> Self {
+        report.into_inner()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl From<Report> for std::error::Report<io::Error> {
+    fn from(report: Report) -> Self {
+        report.into_std()
+    }
+}
+
+/// A piece of a report, passed through the sanitizer, if any.
+struct Clean<'a, T: ?Sized>(&'a T, Option<&'a (dyn Sanitizer + Send + Sync)>);
+
+impl<T: fmt::Display + ?Sized> fmt::Display for Clean<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(sanitizer) => f.write_str(&sanitizer.sanitize(&self.0.to_string())),
+            None => self.0.fmt(f),
+        }
+    }
+}
+
+fn render(
+    error: &io::Error,
+    f: &mut fmt::Formatter<'_>,
+    pretty: bool,
+    sanitizer: Option<&(dyn Sanitizer + Send + Sync)>,
+) -> fmt::Result {
+    write!(f, "{}", Clean(error, sanitizer))?;
+    let causes = Chain::new(error.source());
+    match pretty {
+        false => {
+            for cause in causes {
+                write!(f, ": {}", Clean(cause, sanitizer))?
+            }
+        }
+        true => {
+            let causes = causes.collect::<Vec<_>>();
+            if !causes.is_empty() {
+                f.write_str("\n\nCaused by:")?
+            }
+            for (ix, cause) in causes.iter().enumerate() {
+                let cause = Clean(*cause, sanitizer)
+                    .to_string()
+                    .replace('\n', "\n      ");
+                match causes.len() {
+                    1 => write!(f, "\n      {}", cause)?,
+                    _ => write!(f, "\n{:>4}: {}", ix, cause)?,
+                }
+            }
+        }
+    }
+    let hints = crate::hints(error).map(|it| ("hint", it));
+    let help = crate::help_of(error).map(|it| ("help", it));
+    for (ix, (label, text)) in hints.chain(help).enumerate() {
+        match (pretty, ix) {
+            (true, 0) => write!(f, "\n\n{}: {}", label, Clean(text, sanitizer))?,
+            _ => write!(f, "\n{}: {}", label, Clean(text, sanitizer))?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(
+            &self.error,
+            f,
+            self.pretty || f.alternate(),
+            self.sanitizer.as_deref(),
+        )
+    }
+}
+
+/// The pretty rendering, so that a [`Report`] returned from `main` shows each
+/// cause.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{IoErrorExt as _, Report};
+///
+/// let e = io::Error::not_found("no such file").context("reading config");
+/// assert_eq!(
+///     format!("{:?}", Report::new(e)),
+///     "\
+/// reading config
+///
+/// Caused by:
+///       no such file"
+/// );
+/// ```
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(&self.error, f, true, self.sanitizer.as_deref())
+    }
+}
+
+/// Install a panic hook which renders [`io::Error`] and [`Report`] payloads
+/// as a pretty [`Report`], with every cause, deferring to the previous hook
+/// for other panics.
+///
+/// Such payloads come from [`panic_any`](panic::panic_any).
+/// Note that [`Result::unwrap`] panics with a message which has already been
+/// formatted, so cannot be rendered this way, unless the error is a [`Report`].
+///
+/// ```
+/// use std::{io, panic};
+/// use io_extra::{install_panic_hook, IoErrorExt as _};
+///
+/// install_panic_hook();
+/// let res = panic::catch_unwind(|| {
+///     panic::panic_any(io::Error::not_found("no such file").context("reading config"))
+/// });
+/// // Printed:
+/// // thread 'main' panicked at src/main.rs:7:5:
+/// // reading config
+/// //
+/// // Caused by:
+/// //       no such file
+/// assert!(res.is_err());
+/// ```
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let error = match payload.downcast_ref::<Report>() {
+            Some(it) => it.get_ref(),
+            None => match payload.downcast_ref::<io::Error>() {
+                Some(it) => it,
+                None => return previous(info),
+            },
+        };
+        let thread = thread::current();
+        let name = thread.name().unwrap_or("<unnamed>");
+        match info.location() {
+            Some(location) => eprintln!(
+                "thread '{}' panicked at {}:\n{}",
+                name,
+                location,
+                Pretty(error)
+            ),
+            None => eprintln!("thread '{}' panicked:\n{}", name, Pretty(error)),
+        }
+    }))
+}
+
+/// The pretty rendering of a borrowed error.
+struct Pretty<'a>(&'a io::Error);
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(self.0, f, true, None)
+    }
+}
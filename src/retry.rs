@@ -0,0 +1,312 @@
+//! Deciding whether, and when, to retry failed operations.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher as _, Hasher as _},
+    io::{self, ErrorKind, ErrorKind::*},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{context::context_fields, IoErrorExt as _};
+
+/// A suggested delay before retrying the operation which produced `e`, or `None`
+/// if it is not worth retrying.
+///
+/// A server-provided hint takes precedence: a `retry_after` [field](crate::fields())
+/// in (possibly fractional) seconds, or a `retry_after_ms` field in milliseconds.
+/// Otherwise, the delay is derived from the kind:
+///
+/// | Kind | Delay |
+/// | ---- | ----- |
+/// | [`Interrupted`] | none |
+/// | [`WouldBlock`] | 10ms |
+/// | [`ConnectionReset`], [`ConnectionAborted`], [`BrokenPipe`] | 100ms |
+/// | [`TimedOut`] | 1s |
+/// | [`ConnectionRefused`], [`NotConnected`], [`AddrInUse`] | 5s |
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::{ctxt, retry};
+///
+/// let e = io::Error::from(io::ErrorKind::TimedOut);
+/// assert_eq!(retry::retry_after(&e), Some(Duration::from_secs(1)));
+///
+/// let e = io::Error::from(io::ErrorKind::NotFound);
+/// assert_eq!(retry::retry_after(&e), None);
+///
+/// // A hint from the server wins.
+/// let retry_after = "2.5";
+/// let e = Err::<(), _>(io::Error::from(io::ErrorKind::WouldBlock))
+///     .map_err(ctxt!("throttled, retry after {retry_after}s", retry_after))
+///     .unwrap_err();
+/// assert_eq!(retry::retry_after(&e), Some(Duration::from_millis(2500)));
+/// ```
+pub fn retry_after(e: &io::Error) -> Option<Duration> {
+    hint(e).or_else(|| kind_delay(e.kind()))
+}
+
+/// A delay requested by the server, in the fields of `e`.
+fn hint(e: &io::Error) -> Option<Duration> {
+    for (key, value) in crate::fields(e) {
+        let hint = match key {
+            "retry_after" => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|it| Duration::try_from_secs_f64(it).ok()),
+            "retry_after_ms" => value.trim().parse().ok().map(Duration::from_millis),
+            _ => None,
+        };
+        if hint.is_some() {
+            return hint;
+        }
+    }
+    None
+}
+
+/// The default delay before retrying an error of `kind`, if it is transient.
+fn kind_delay(kind: ErrorKind) -> Option<Duration> {
+    let millis = match kind {
+        Interrupted => 0,
+        WouldBlock => 10,
+        ConnectionReset | ConnectionAborted | BrokenPipe => 100,
+        TimedOut => 1_000,
+        ConnectionRefused | NotConnected | AddrInUse => 5_000,
+        _ => return None,
+    };
+    Some(Duration::from_millis(millis))
+}
+
+/// How long to wait between attempts, for a [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backoff {
+    /// The same delay before every retry.
+    Fixed(Duration),
+    /// A delay of `initial`, doubling after every retry, up to `max`.
+    Exponential {
+        /// The delay before the first retry.
+        initial: Duration,
+        /// The longest delay.
+        max: Duration,
+    },
+}
+
+/// Which failures to retry, and how, for [`retry`].
+///
+/// By default, up to 3 attempts are made, retrying any error with a delay
+/// from [`retry_after`], and with exponential backoff from 100ms to 10s, with
+/// jitter.
+/// [`Interrupted`] errors are retried immediately.
+/// A delay requested by the server, as described in [`retry_after`], takes
+/// precedence over the backoff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: bool,
+    /// [`None`] for kinds with a delay from [`retry_after`].
+    kinds: Option<Vec<ErrorKind>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Backoff::Exponential {
+                initial: Duration::from_millis(100),
+                max: Duration::from_secs(10),
+            },
+            jitter: true,
+            kinds: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The [default](Default) policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Make at most `max_attempts` attempts (including the first).
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+    /// Wait according to `backoff` between attempts.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    /// Wait for a random fraction of each delay, so that many clients failing
+    /// at once don't retry in lockstep.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+    /// Retry only errors of the given kinds.
+    pub fn retry_on(mut self, kinds: impl IntoIterator<Item = ErrorKind>) -> Self {
+        self.kinds = Some(kinds.into_iter().collect());
+        self
+    }
+    /// Whether this policy retries errors of `kind`.
+    pub fn retries(&self, kind: ErrorKind) -> bool {
+        match &self.kinds {
+            Some(kinds) => kinds.contains(&kind),
+            None => kind_delay(kind).is_some(),
+        }
+    }
+    /// The delay before retrying after `e`, which failed attempt number
+    /// `attempt` (from 1).
+    pub fn delay(&self, e: &io::Error, attempt: u32) -> Duration {
+        if let Some(hint) = hint(e) {
+            return hint;
+        }
+        if e.kind() == Interrupted {
+            return Duration::ZERO;
+        }
+        let delay = match self.backoff {
+            Backoff::Fixed(it) => it,
+            Backoff::Exponential { initial, max } => initial
+                .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+                .unwrap_or(max)
+                .min(max),
+        };
+        match self.jitter {
+            true => jitter(delay),
+            false => delay,
+        }
+    }
+}
+
+/// A random duration up to `max`.
+fn jitter(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    Duration::from_nanos(random % nanos.saturating_add(1))
+}
+
+/// Call `f` until it succeeds, fails with an error the `policy` doesn't
+/// [retry](RetryPolicy::retries), or runs out of attempts.
+///
+/// If it fails after more than one attempt, the error is given the context
+/// "giving up after `n` attempts", with `n` as an `attempts`
+/// [field](crate::fields()).
+/// Either way, the history of failed attempts may be retrieved with
+/// [`attempts_of`].
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::{fields, retry::{retry, Backoff, RetryPolicy}};
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(4)
+///     .backoff(Backoff::Fixed(Duration::ZERO));
+///
+/// let mut calls = 0;
+/// let res = retry(&policy, || {
+///     calls += 1;
+///     match calls {
+///         3 => Ok("connected"),
+///         _ => Err(io::Error::from(io::ErrorKind::ConnectionReset)),
+///     }
+/// });
+/// assert_eq!(res?, "connected");
+///
+/// let e = retry(&policy, || Err::<(), _>(io::Error::new(io::ErrorKind::TimedOut, "no response")))
+///     .unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+/// assert_eq!(format!("{:#}", e), "giving up after 4 attempts: no response");
+/// assert!(fields(&e).eq([("attempts", "4")]));
+///
+/// // Not worth retrying.
+/// let e = retry(&policy, || Err::<(), _>(io::Error::from(io::ErrorKind::NotFound))).unwrap_err();
+/// assert_eq!(e.to_string(), "entity not found");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn retry<T>(policy: &RetryPolicy, mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut attempts = Vec::new();
+    loop {
+        let e = match f() {
+            Ok(it) => return Ok(it),
+            Err(e) => e,
+        };
+        let at = SystemTime::now();
+        let attempt = attempts.len() as u32 + 1;
+        if attempt >= policy.max_attempts || !policy.retries(e.kind()) {
+            attempts.push(Attempt {
+                at,
+                kind: e.kind(),
+                delay: Duration::ZERO,
+            });
+            let e = match attempt {
+                1 => e,
+                _ => context_fields(
+                    e,
+                    format_args!("giving up after {} attempts", attempt),
+                    [("attempts", attempt)],
+                ),
+            };
+            return Err(e.with_payload(Attempts(attempts)));
+        }
+        let delay = policy.delay(&e, attempt);
+        attempts.push(Attempt {
+            at,
+            kind: e.kind(),
+            delay,
+        });
+        thread::sleep(delay);
+    }
+}
+
+/// A failed attempt, as recorded by [`retry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Attempt {
+    /// When the attempt failed.
+    pub at: SystemTime,
+    /// The kind of error it failed with.
+    pub kind: ErrorKind,
+    /// How long [`retry`] waited before the next attempt, which is zero for the
+    /// last.
+    pub delay: Duration,
+}
+
+#[derive(Debug)]
+struct Attempts(Vec<Attempt>);
+
+/// The failed attempts, oldest first, of the [`retry`] which returned `e`.
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::retry::{attempts_of, retry, Backoff, RetryPolicy};
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(3)
+///     .backoff(Backoff::Fixed(Duration::from_millis(1)))
+///     .jitter(false);
+///
+/// let mut calls = 0;
+/// let e = retry(&policy, || {
+///     calls += 1;
+///     match calls {
+///         1 => Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionReset)),
+///         _ => Err(io::Error::from(io::ErrorKind::TimedOut)),
+///     }
+/// })
+/// .unwrap_err();
+///
+/// let attempts = attempts_of(&e).unwrap();
+/// assert!(attempts.iter().map(|it| it.kind).eq([
+///     io::ErrorKind::ConnectionReset,
+///     io::ErrorKind::TimedOut,
+///     io::ErrorKind::TimedOut,
+/// ]));
+/// assert!(attempts.iter().map(|it| it.delay.as_millis()).eq([1, 1, 0]));
+/// assert!(attempts[0].at < attempts[2].at);
+///
+/// assert!(attempts_of(&io::Error::from(io::ErrorKind::TimedOut)).is_none());
+/// ```
+pub fn attempts_of(e: &io::Error) -> Option<&[Attempt]> {
+    crate::payload_of::<Attempts>(e).map(|it| &*it.0)
+}
@@ -0,0 +1,179 @@
+use std::{
+    borrow::Cow,
+    collections::hash_map::DefaultHasher,
+    env, fmt,
+    hash::{Hash as _, Hasher as _},
+    io,
+    net::Ipv4Addr,
+};
+
+use crate::context::Chain;
+
+/// Rewrites the text of error messages before they are displayed, so that errors
+/// can be shown to users or sent to telemetry without leaking personal information.
+///
+/// See [`Redact`] for a configurable implementation, and
+/// [`IoErrorExt::sanitized`](crate::IoErrorExt::sanitized) and
+/// [`Report::sanitized`](crate::Report::sanitized) for applying it.
+pub trait Sanitizer {
+    /// Sanitize the message of a single frame in an error chain.
+    fn sanitize<'a>(&self, text: &'a str) -> Cow<'a, str>;
+}
+
+impl<F: Fn(&str) -> String> Sanitizer for F {
+    fn sanitize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(text))
+    }
+}
+
+/// A [`Sanitizer`] which redacts literal patterns and IP addresses.
+///
+/// Redacted text is replaced with `<redacted>`, or, if [hashing](Self::hash),
+/// with a short hash of the text, so that occurrences can still be correlated.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{IoErrorExt as _, Redact};
+///
+/// let e = io::Error::connection_refused("couldn't reach 10.0.0.1 with token hunter2")
+///     .context("syncing /home/ferris/notes");
+/// let redact = Redact::new()
+///     .replace("/home/ferris", "~")
+///     .literal("hunter2")
+///     .ipv4();
+/// assert_eq!(
+///     format!("{:#}", e.sanitized(&redact)),
+///     "syncing ~/notes: couldn't reach <redacted> with token <redacted>",
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Redact {
+    replacements: Vec<(String, Option<String>)>,
+    ipv4: bool,
+    hash: bool,
+}
+
+impl Redact {
+    /// A sanitizer which redacts nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Redact occurrences of `pattern`.
+    pub fn literal(mut self, pattern: impl Into<String>) -> Self {
+        self.replacements.push((pattern.into(), None));
+        self
+    }
+    /// Replace occurrences of `pattern` with `with`.
+    pub fn replace(mut self, pattern: impl Into<String>, with: impl Into<String>) -> Self {
+        self.replacements.push((pattern.into(), Some(with.into())));
+        self
+    }
+    /// Replace the current user's home directory with `~`.
+    pub fn home_dir(self) -> Self {
+        match env::var("HOME").or_else(|_| env::var("USERPROFILE")) {
+            Ok(home) if !home.is_empty() => self.replace(home, "~"),
+            _ => self,
+        }
+    }
+    /// Redact IPv4 addresses.
+    pub fn ipv4(mut self) -> Self {
+        self.ipv4 = true;
+        self
+    }
+    /// Replace redacted text with a short hash of it, rather than `<redacted>`.
+    pub fn hash(mut self, hash: bool) -> Self {
+        self.hash = hash;
+        self
+    }
+    fn redacted(&self, text: &str) -> String {
+        match self.hash {
+            true => {
+                let mut hasher = DefaultHasher::new();
+                text.hash(&mut hasher);
+                format!("<redacted:{:08x}>", hasher.finish() as u32)
+            }
+            false => String::from("<redacted>"),
+        }
+    }
+}
+
+impl Sanitizer for Redact {
+    fn sanitize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (pattern, with) in &self.replacements {
+            if !pattern.is_empty() && text.contains(pattern.as_str()) {
+                let with = match with {
+                    Some(with) => with.clone(),
+                    None => self.redacted(pattern),
+                };
+                text = Cow::Owned(text.replace(pattern.as_str(), &with))
+            }
+        }
+        if self.ipv4 {
+            let mut out = String::with_capacity(text.len());
+            let mut rest = &*text;
+            while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+                out.push_str(&rest[..start]);
+                rest = &rest[start..];
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                let run = &rest[..end];
+                match run.trim_end_matches('.').parse::<Ipv4Addr>() {
+                    Ok(_) => {
+                        let addr = run.trim_end_matches('.');
+                        out.push_str(&self.redacted(addr));
+                        out.push_str(&run[addr.len()..]);
+                    }
+                    Err(_) => out.push_str(run),
+                }
+                rest = &rest[end..];
+            }
+            out.push_str(rest);
+            if out != *text {
+                text = Cow::Owned(out)
+            }
+        }
+        text
+    }
+}
+
+/// Displays an [`io::Error`] with every frame passed through a [`Sanitizer`].
+///
+/// The alternate representation (`{:#}`) includes the full chain of causes.
+/// The [`Debug`](fmt::Debug) representation is sanitized too:
+///
+/// ```
+/// use io_extra::{IoErrorExt as _, Redact};
+///
+/// let e = std::io::Error::other("bad token hunter2");
+/// let redact = Redact::new().literal("hunter2");
+/// assert!(!format!("{:?}", e.sanitized(&redact)).contains("hunter2"));
+/// ```
+///
+/// Created by [`IoErrorExt::sanitized`](crate::IoErrorExt::sanitized).
+pub struct Sanitized<'a, S: ?Sized> {
+    pub(crate) error: &'a io::Error,
+    pub(crate) sanitizer: &'a S,
+}
+
+impl<S: Sanitizer + ?Sized> fmt::Display for Sanitized<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sanitizer.sanitize(&self.error.to_string()))?;
+        if f.alternate() {
+            for parent in Chain::new(std::error::Error::source(self.error)) {
+                write!(f, ": {}", self.sanitizer.sanitize(&parent.to_string()))?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shows the sanitized chain, never the raw error.
+impl<S: Sanitizer + ?Sized> fmt::Debug for Sanitized<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sanitized")
+            .field("error", &format!("{:#}", self))
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,105 @@
+use std::{error::Error as _, fmt, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::{context, Chain};
+
+/// A serializable snapshot of an [`io::Error`], for sending errors across
+/// process boundaries.
+///
+/// The representation is suitable for non-self-describing formats like
+/// [bincode](https://docs.rs/bincode) and [postcard](https://docs.rs/postcard):
+/// fields are always present, in a fixed order, beginning with a
+/// [`version`](Self::version).
+///
+/// ```
+/// use std::io;
+/// use io_extra::{IoErrorExt as _, SerializableError};
+///
+/// let e = io::Error::not_found("no such table").context("loading schema");
+/// let bytes = postcard::to_allocvec(&SerializableError::from(&e)).unwrap();
+///
+/// let e = io::Error::from(postcard::from_bytes::<SerializableError>(&bytes).unwrap());
+/// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+/// assert_eq!(format!("{:#}", e), "loading schema: no such table");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SerializableError {
+    /// The version of this representation, currently [`SerializableError::VERSION`].
+    pub version: u32,
+    /// The [`Debug`](std::fmt::Debug) representation of the [`io::ErrorKind`],
+    /// e.g `"NotFound"`.
+    pub kind: String,
+    /// The outermost message.
+    pub message: String,
+    /// The messages of each cause, outermost first.
+    pub causes: Vec<String>,
+    /// The OS error code in the chain, if any (see [`os_error_code`](crate::os_error_code)),
+    /// as reported by the sending platform.
+    pub os_code: Option<i32>,
+}
+
+impl SerializableError {
+    /// The current version of the representation.
+    pub const VERSION: u32 = 1;
+}
+
+impl From<&io::Error> for SerializableError {
+    fn from(e: &io::Error) -> Self {
+        Self {
+            version: Self::VERSION,
+            kind: format!("{:?}", e.kind()),
+            message: e.to_string(),
+            causes: Chain::new(e.source()).map(|it| it.to_string()).collect(),
+            os_code: crate::os_error_code(e),
+        }
+    }
+}
+
+/// The outermost message, followed by the causes in the alternate form, as with
+/// [`io::Error`]s from this crate.
+impl fmt::Display for SerializableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if f.alternate() {
+            for cause in &self.causes {
+                write!(f, ": {}", cause)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild an error with the same kind and messages.
+///
+/// Unknown kinds become [`io::ErrorKind::Other`].
+/// The [`os_code`](SerializableError::os_code) is only informational, and isn't
+/// reattached: the kind is what was sent, and codes mean different things on
+/// different platforms.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{os_error_code, IoErrorExt as _, SerializableError};
+///
+/// let e = io::Error::from_raw_os_error(2).with_kind(io::ErrorKind::InvalidInput);
+/// let sent = SerializableError::from(&e);
+/// assert_eq!(sent.os_code, Some(2));
+///
+/// let e = io::Error::from(sent);
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+/// assert_eq!(os_error_code(&e), None);
+/// ```
+impl From<SerializableError> for io::Error {
+    fn from(value: SerializableError) -> Self {
+        let kind = crate::kind::from_name(&value.kind).unwrap_or(io::ErrorKind::Other);
+        let mut messages = value.causes;
+        messages.insert(0, value.message);
+        let mut messages = messages.into_iter().rev();
+        let innermost = match messages.next() {
+            Some(message) => io::Error::new(kind, message),
+            None => io::Error::from(kind),
+        };
+        messages.fold(innermost, context)
+    }
+}
@@ -0,0 +1,143 @@
+//! Mapping between [`ErrorKind`]s and SFTP status codes, as defined by
+//! [draft-ietf-secsh-filexfer](https://datatracker.ietf.org/doc/html/draft-ietf-secsh-filexfer-13#section-9.1).
+//!
+//! ```
+//! use std::io;
+//! use io_extra::sftp;
+//!
+//! assert_eq!(sftp::status(io::ErrorKind::NotFound), sftp::SSH_FX_NO_SUCH_FILE);
+//! assert_eq!(sftp::kind(sftp::SSH_FX_PERMISSION_DENIED), Some(io::ErrorKind::PermissionDenied));
+//! assert_eq!(sftp::kind(sftp::SSH_FX_OK), None);
+//!
+//! // Filesystem conditions round-trip.
+//! for status in [
+//!     sftp::SSH_FX_NO_SPACE_ON_FILESYSTEM,
+//!     sftp::SSH_FX_QUOTA_EXCEEDED,
+//!     sftp::SSH_FX_DIR_NOT_EMPTY,
+//!     sftp::SSH_FX_NOT_A_DIRECTORY,
+//! ] {
+//!     assert_eq!(sftp::status(sftp::kind(status).unwrap()), status);
+//! }
+//! assert_eq!(sftp::kind(sftp::SSH_FX_NO_SPACE_ON_FILESYSTEM), Some(io::ErrorKind::StorageFull));
+//! ```
+
+use std::io::ErrorKind::{self, *};
+
+use crate::kind::newer_kinds;
+
+newer_kinds! {
+    #[cfg(io_error_more)] READ_ONLY_FILESYSTEM = ReadOnlyFilesystem else PermissionDenied;
+    #[cfg(io_error_invalid_filename)] INVALID_FILENAME = InvalidFilename else InvalidInput;
+    #[cfg(io_error_more)] STORAGE_FULL = StorageFull else Other;
+    #[cfg(io_error_quota_exceeded)] QUOTA_EXCEEDED = QuotaExceeded else Other;
+    #[cfg(io_error_more)] RESOURCE_BUSY = ResourceBusy else Other;
+    #[cfg(io_error_more)] DIRECTORY_NOT_EMPTY = DirectoryNotEmpty else Other;
+    #[cfg(io_error_more)] NOT_A_DIRECTORY = NotADirectory else Other;
+    #[cfg(feature = "nightly")] FILESYSTEM_LOOP = FilesystemLoop else Other;
+}
+
+/// The operation succeeded.
+pub const SSH_FX_OK: u32 = 0;
+/// End of file, or no more directory entries.
+pub const SSH_FX_EOF: u32 = 1;
+/// A reference was made to a file which does not exist.
+pub const SSH_FX_NO_SUCH_FILE: u32 = 2;
+/// The user does not have sufficient permissions.
+pub const SSH_FX_PERMISSION_DENIED: u32 = 3;
+/// An error occurred, but no more specific code is defined.
+pub const SSH_FX_FAILURE: u32 = 4;
+/// A badly formatted packet or protocol incompatibility was detected.
+pub const SSH_FX_BAD_MESSAGE: u32 = 5;
+/// There is no connection to the server.
+pub const SSH_FX_NO_CONNECTION: u32 = 6;
+/// The connection to the server was lost.
+pub const SSH_FX_CONNECTION_LOST: u32 = 7;
+/// The server does not support the operation.
+pub const SSH_FX_OP_UNSUPPORTED: u32 = 8;
+/// The handle value was invalid.
+pub const SSH_FX_INVALID_HANDLE: u32 = 9;
+/// The file path does not exist or is invalid.
+pub const SSH_FX_NO_SUCH_PATH: u32 = 10;
+/// The file already exists.
+pub const SSH_FX_FILE_ALREADY_EXISTS: u32 = 11;
+/// The file is on read-only media, or the media is write protected.
+pub const SSH_FX_WRITE_PROTECT: u32 = 12;
+/// There is no media available in the drive.
+pub const SSH_FX_NO_MEDIA: u32 = 13;
+/// There is insufficient free space on the filesystem.
+pub const SSH_FX_NO_SPACE_ON_FILESYSTEM: u32 = 14;
+/// The user's storage quota would be exceeded.
+pub const SSH_FX_QUOTA_EXCEEDED: u32 = 15;
+/// A principal referenced by the request is unknown.
+pub const SSH_FX_UNKNOWN_PRINCIPAL: u32 = 16;
+/// The file could not be opened because it is locked by another process.
+pub const SSH_FX_LOCK_CONFLICT: u32 = 17;
+/// The directory is not empty.
+pub const SSH_FX_DIR_NOT_EMPTY: u32 = 18;
+/// The specified file is not a directory.
+pub const SSH_FX_NOT_A_DIRECTORY: u32 = 19;
+/// The filename is not valid.
+pub const SSH_FX_INVALID_FILENAME: u32 = 20;
+/// Too many symbolic links were encountered.
+pub const SSH_FX_LINK_LOOP: u32 = 21;
+
+/// The SFTP status code to report for errors of `kind`.
+///
+/// Kinds without a more specific code are reported as [`SSH_FX_FAILURE`].
+pub fn status(kind: ErrorKind) -> u32 {
+    match kind {
+        UnexpectedEof => SSH_FX_EOF,
+        NotFound => SSH_FX_NO_SUCH_FILE,
+        PermissionDenied => SSH_FX_PERMISSION_DENIED,
+        InvalidData => SSH_FX_BAD_MESSAGE,
+        NotConnected => SSH_FX_NO_CONNECTION,
+        ConnectionAborted | ConnectionReset | BrokenPipe => SSH_FX_CONNECTION_LOST,
+        Unsupported => SSH_FX_OP_UNSUPPORTED,
+        AlreadyExists => SSH_FX_FILE_ALREADY_EXISTS,
+        #[cfg(io_error_more)]
+        ReadOnlyFilesystem => SSH_FX_WRITE_PROTECT,
+        #[cfg(io_error_more)]
+        StorageFull => SSH_FX_NO_SPACE_ON_FILESYSTEM,
+        #[cfg(io_error_quota_exceeded)]
+        QuotaExceeded => SSH_FX_QUOTA_EXCEEDED,
+        #[cfg(io_error_more)]
+        ResourceBusy => SSH_FX_LOCK_CONFLICT,
+        #[cfg(io_error_more)]
+        DirectoryNotEmpty => SSH_FX_DIR_NOT_EMPTY,
+        #[cfg(io_error_more)]
+        NotADirectory => SSH_FX_NOT_A_DIRECTORY,
+        #[cfg(io_error_invalid_filename)]
+        InvalidFilename => SSH_FX_INVALID_FILENAME,
+        #[cfg(feature = "nightly")]
+        FilesystemLoop => SSH_FX_LINK_LOOP,
+        _ => SSH_FX_FAILURE,
+    }
+}
+
+/// The [`ErrorKind`] for an SFTP status code, or [`None`] for [`SSH_FX_OK`].
+///
+/// Unknown codes are mapped to [`ErrorKind::Other`].
+pub fn kind(status: u32) -> Option<ErrorKind> {
+    let kind = match status {
+        SSH_FX_OK => return None,
+        SSH_FX_EOF => UnexpectedEof,
+        SSH_FX_NO_SUCH_FILE | SSH_FX_NO_SUCH_PATH => NotFound,
+        SSH_FX_PERMISSION_DENIED => PermissionDenied,
+        SSH_FX_WRITE_PROTECT => READ_ONLY_FILESYSTEM,
+        SSH_FX_BAD_MESSAGE => InvalidData,
+        SSH_FX_NO_CONNECTION => NotConnected,
+        SSH_FX_CONNECTION_LOST => ConnectionAborted,
+        SSH_FX_OP_UNSUPPORTED => Unsupported,
+        SSH_FX_INVALID_HANDLE => InvalidInput,
+        SSH_FX_INVALID_FILENAME => INVALID_FILENAME,
+        SSH_FX_FILE_ALREADY_EXISTS => AlreadyExists,
+        SSH_FX_NO_SPACE_ON_FILESYSTEM => STORAGE_FULL,
+        SSH_FX_QUOTA_EXCEEDED => QUOTA_EXCEEDED,
+        SSH_FX_LOCK_CONFLICT => RESOURCE_BUSY,
+        SSH_FX_DIR_NOT_EMPTY => DIRECTORY_NOT_EMPTY,
+        SSH_FX_NOT_A_DIRECTORY => NOT_A_DIRECTORY,
+        SSH_FX_LINK_LOOP => FILESYSTEM_LOOP,
+        _ => Other,
+    };
+    Some(kind)
+}
@@ -0,0 +1,83 @@
+use std::{
+    io::{
+        self,
+        ErrorKind::{InvalidInput, UnexpectedEof},
+    },
+    ops::{Bound, RangeBounds},
+};
+
+/// Split `buf` at `n`, failing with [`UnexpectedEof`] if it is shorter than `n`,
+/// rather than panicking like [`slice::split_at`].
+///
+/// ```
+/// use std::io;
+/// use io_extra::split_at_io;
+///
+/// let (header, body) = split_at_io(b"\x01\x02rest", 2)?;
+/// assert_eq!((header, body), (&b"\x01\x02"[..], &b"rest"[..]));
+///
+/// let e = split_at_io(b"\x01", 2).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(e.to_string(), "expected at least 2 bytes, but only 1 are available");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn split_at_io(buf: &[u8], n: usize) -> io::Result<(&[u8], &[u8])> {
+    match buf.len() >= n {
+        true => Ok(buf.split_at(n)),
+        false => Err(io::Error::new(
+            UnexpectedEof,
+            format!(
+                "expected at least {} bytes, but only {} are available",
+                n,
+                buf.len()
+            ),
+        )),
+    }
+}
+
+/// Get the subslice of `buf` at `range`, failing with [`UnexpectedEof`] if it
+/// extends past the end, rather than panicking like indexing.
+///
+/// Ranges which end before they start are [`InvalidInput`].
+///
+/// ```
+/// use std::io;
+/// use io_extra::get_io;
+///
+/// assert_eq!(get_io(b"hello", 1..3)?, b"el");
+/// assert_eq!(get_io(b"hello", 3..)?, b"lo");
+///
+/// let e = get_io(b"hello", 3..8).unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(e.to_string(), "requested bytes 3..8, but only 5 are available");
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn get_io(buf: &[u8], range: impl RangeBounds<usize>) -> io::Result<&[u8]> {
+    // Widen, so that inclusive bounds can't overflow.
+    let start = match range.start_bound() {
+        Bound::Included(&it) => it as u128,
+        Bound::Excluded(&it) => it as u128 + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&it) => it as u128 + 1,
+        Bound::Excluded(&it) => it as u128,
+        Bound::Unbounded => buf.len() as u128,
+    };
+    match (start <= end, end <= buf.len() as u128) {
+        (false, _) => Err(io::Error::new(
+            InvalidInput,
+            format!("invalid range {}..{}", start, end),
+        )),
+        (true, true) => Ok(&buf[start as usize..end as usize]),
+        (true, false) => Err(io::Error::new(
+            UnexpectedEof,
+            format!(
+                "requested bytes {}..{}, but only {} are available",
+                start,
+                end,
+                buf.len()
+            ),
+        )),
+    }
+}
@@ -0,0 +1,135 @@
+//! Mapping between [`io::Error`]s and the `NTSTATUS` codes used by SMB.
+//!
+//! ```
+//! use std::io;
+//! use io_extra::{smb, IoErrorExt as _};
+//!
+//! let e = smb::error(smb::STATUS_OBJECT_PATH_NOT_FOUND).context("opening share");
+//! assert_eq!(e.kind(), io::ErrorKind::NotFound);
+//! assert_eq!(format!("{:#}", e), "opening share: STATUS_OBJECT_PATH_NOT_FOUND (0xC000003A)");
+//!
+//! // The original status round-trips, rather than the usual one for the kind.
+//! assert_eq!(smb::status_of(&e), smb::STATUS_OBJECT_PATH_NOT_FOUND);
+//! assert_eq!(smb::status_of(&io::Error::not_found("gone")), smb::STATUS_OBJECT_NAME_NOT_FOUND);
+//!
+//! // Filesystem conditions map to their own kinds, in both directions.
+//! for status in [
+//!     smb::STATUS_DISK_FULL,
+//!     smb::STATUS_FILE_IS_A_DIRECTORY,
+//!     smb::STATUS_DIRECTORY_NOT_EMPTY,
+//!     smb::STATUS_NOT_A_DIRECTORY,
+//! ] {
+//!     assert_eq!(smb::status(smb::kind(status).unwrap()), status);
+//! }
+//! assert_eq!(smb::error(smb::STATUS_DISK_FULL).kind(), io::ErrorKind::StorageFull);
+//! ```
+
+use std::io::{self, ErrorKind::*};
+
+use crate::kind::{newer_kinds, statuses};
+
+newer_kinds! {
+    #[cfg(io_error_invalid_filename)] INVALID_FILENAME = InvalidFilename else InvalidInput;
+    #[cfg(io_error_more)] RESOURCE_BUSY = ResourceBusy else Other;
+    #[cfg(io_error_more)] STORAGE_FULL = StorageFull else Other;
+    #[cfg(io_error_more)] IS_A_DIRECTORY = IsADirectory else Other;
+    #[cfg(io_error_more)] DIRECTORY_NOT_EMPTY = DirectoryNotEmpty else Other;
+    #[cfg(io_error_more)] NOT_A_DIRECTORY = NotADirectory else Other;
+}
+
+statuses! {
+    /// An `NTSTATUS` code, as the payload of an [`io::Error`].
+    pub struct NtStatus;
+
+    /// The operation completed successfully.
+    STATUS_SUCCESS = 0x0000_0000;
+
+    display = "{} (0x{:08X})", "{=str} ({=u32:#010x})";
+    unknown = "NTSTATUS 0x{:08X}", "NTSTATUS {=u32:#010x}";
+
+    /// The operation failed.
+    STATUS_UNSUCCESSFUL = 0xC000_0001 => Other;
+    /// The requested operation is not implemented.
+    STATUS_NOT_IMPLEMENTED = 0xC000_0002 => Unsupported;
+    /// An invalid handle was specified.
+    STATUS_INVALID_HANDLE = 0xC000_0008 => InvalidInput;
+    /// An invalid parameter was passed to a service or function.
+    STATUS_INVALID_PARAMETER = 0xC000_000D => InvalidInput;
+    /// The file does not exist.
+    STATUS_NO_SUCH_FILE = 0xC000_000F => NotFound;
+    /// The end of the file was reached.
+    STATUS_END_OF_FILE = 0xC000_0011 => UnexpectedEof;
+    /// Not enough virtual memory or paging file quota is available.
+    STATUS_NO_MEMORY = 0xC000_0017 => OutOfMemory;
+    /// A process has requested access to an object but has not been granted those access rights.
+    STATUS_ACCESS_DENIED = 0xC000_0022 => PermissionDenied;
+    /// The object name is invalid.
+    STATUS_OBJECT_NAME_INVALID = 0xC000_0033 => INVALID_FILENAME;
+    /// The object name is not found.
+    STATUS_OBJECT_NAME_NOT_FOUND = 0xC000_0034 => NotFound;
+    /// The object name already exists.
+    STATUS_OBJECT_NAME_COLLISION = 0xC000_0035 => AlreadyExists;
+    /// The path does not exist.
+    STATUS_OBJECT_PATH_NOT_FOUND = 0xC000_003A => NotFound;
+    /// A file cannot be opened because the share access flags are incompatible.
+    STATUS_SHARING_VIOLATION = 0xC000_0043 => RESOURCE_BUSY;
+    /// The disk is full.
+    STATUS_DISK_FULL = 0xC000_007F => STORAGE_FULL;
+    /// The file that was specified as a target is a directory.
+    STATUS_FILE_IS_A_DIRECTORY = 0xC000_00BA => IS_A_DIRECTORY;
+    /// The request is not supported.
+    STATUS_NOT_SUPPORTED = 0xC000_00BB => Unsupported;
+    /// The specified I/O operation was not completed before the time-out period expired.
+    STATUS_IO_TIMEOUT = 0xC000_00B5 => TimedOut;
+    /// The network name was deleted.
+    STATUS_NETWORK_NAME_DELETED = 0xC000_00C9 => NotConnected;
+    /// The directory is not empty.
+    STATUS_DIRECTORY_NOT_EMPTY = 0xC000_0101 => DIRECTORY_NOT_EMPTY;
+    /// A requested opened file is not a directory.
+    STATUS_NOT_A_DIRECTORY = 0xC000_0103 => NOT_A_DIRECTORY;
+    /// The I/O request was canceled.
+    STATUS_CANCELLED = 0xC000_0120 => Interrupted;
+    /// The pipe operation has failed because the other end of the pipe has been closed.
+    STATUS_PIPE_BROKEN = 0xC000_014B => BrokenPipe;
+    /// The transport connection was reset.
+    STATUS_CONNECTION_RESET = 0xC000_020D => ConnectionReset;
+    /// The transport connection attempt was refused by the remote system.
+    STATUS_CONNECTION_REFUSED = 0xC000_0236 => ConnectionRefused;
+    /// The transport connection was aborted by the local system.
+    STATUS_CONNECTION_ABORTED = 0xC000_0241 => ConnectionAborted;
+}
+
+/// The `NTSTATUS` to report for errors of `kind`.
+///
+/// Kinds without a more specific code are reported as [`STATUS_UNSUCCESSFUL`].
+pub fn status(kind: io::ErrorKind) -> u32 {
+    match kind {
+        NotFound => STATUS_OBJECT_NAME_NOT_FOUND,
+        PermissionDenied => STATUS_ACCESS_DENIED,
+        AlreadyExists => STATUS_OBJECT_NAME_COLLISION,
+        InvalidInput => STATUS_INVALID_PARAMETER,
+        UnexpectedEof => STATUS_END_OF_FILE,
+        OutOfMemory => STATUS_NO_MEMORY,
+        TimedOut => STATUS_IO_TIMEOUT,
+        NotConnected => STATUS_NETWORK_NAME_DELETED,
+        Interrupted => STATUS_CANCELLED,
+        BrokenPipe => STATUS_PIPE_BROKEN,
+        ConnectionReset => STATUS_CONNECTION_RESET,
+        ConnectionRefused => STATUS_CONNECTION_REFUSED,
+        ConnectionAborted => STATUS_CONNECTION_ABORTED,
+        Unsupported => STATUS_NOT_SUPPORTED,
+        #[cfg(io_error_invalid_filename)]
+        InvalidFilename => STATUS_OBJECT_NAME_INVALID,
+        #[cfg(io_error_more)]
+        ResourceBusy => STATUS_SHARING_VIOLATION,
+        #[cfg(io_error_more)]
+        StorageFull => STATUS_DISK_FULL,
+        #[cfg(io_error_more)]
+        IsADirectory => STATUS_FILE_IS_A_DIRECTORY,
+        #[cfg(io_error_more)]
+        DirectoryNotEmpty => STATUS_DIRECTORY_NOT_EMPTY,
+        #[cfg(io_error_more)]
+        NotADirectory => STATUS_NOT_A_DIRECTORY,
+        _ => STATUS_UNSUCCESSFUL,
+    }
+}
@@ -0,0 +1,106 @@
+//! Producing correctly kinded [`io::Error`]s from database drivers, for storage
+//! layers which expose databases through IO traits.
+
+use std::io::{self, ErrorKind::*};
+
+/// The [`ErrorKind`](io::ErrorKind) for a five-character SQLSTATE code, as
+/// reported by PostgreSQL, MySQL, and ODBC drivers.
+///
+/// Retryable failures, like serialization failures and deadlocks, are
+/// [`WouldBlock`].
+/// Unknown codes are mapped by their class, and then to [`Other`].
+///
+/// ```
+/// use std::io;
+/// use io_extra::sql::sqlstate_to_kind;
+///
+/// assert_eq!(sqlstate_to_kind("23505"), io::ErrorKind::AlreadyExists); // unique_violation
+/// assert_eq!(sqlstate_to_kind("40P01"), io::ErrorKind::WouldBlock); // deadlock_detected
+/// assert_eq!(sqlstate_to_kind("28P01"), io::ErrorKind::PermissionDenied); // invalid_password
+/// ```
+pub fn sqlstate_to_kind(sqlstate: &str) -> io::ErrorKind {
+    match sqlstate {
+        "02000" => NotFound,
+        "08001" | "08004" => ConnectionRefused,
+        "08003" => NotConnected,
+        "08006" | "08007" | "57P01" | "57P02" => ConnectionAborted,
+        "23505" => AlreadyExists,
+        "42501" => PermissionDenied,
+        "42P01" | "42S02" | "42704" | "42883" | "3D000" | "58P01" => NotFound,
+        "42P04" | "42P06" | "42P07" | "42S01" | "42710" | "42723" => AlreadyExists,
+        "53200" => OutOfMemory,
+        "53300" | "55P03" => WouldBlock,
+        "57014" => Interrupted,
+        "HYT00" | "HYT01" => TimedOut,
+        _ => match sqlstate.get(..2) {
+            Some("08") => NotConnected,
+            Some("0A") => Unsupported,
+            Some("22" | "23" | "42") => InvalidInput,
+            Some("28") => PermissionDenied,
+            Some("40") => WouldBlock,
+            _ => Other,
+        },
+    }
+}
+
+/// Convert a [`rusqlite::Error`], preserving it as the payload.
+///
+/// Busy and locked databases are [`WouldBlock`], so
+/// that the operation may be retried.
+///
+/// ```
+/// use std::io;
+/// use io_extra::sql::from_rusqlite;
+///
+/// let e = rusqlite::Connection::open_with_flags(
+///     "/nonexistent/db.sqlite",
+///     rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+/// )
+/// .unwrap_err();
+/// assert_eq!(from_rusqlite(e).kind(), io::ErrorKind::NotFound);
+///
+/// let conn = rusqlite::Connection::open_in_memory().unwrap();
+/// let e = conn
+///     .query_row("select 1 where false", [], |row| row.get::<_, i32>(0))
+///     .unwrap_err();
+/// assert_eq!(from_rusqlite(e).kind(), io::ErrorKind::NotFound);
+/// ```
+#[cfg(feature = "rusqlite")]
+pub fn from_rusqlite(e: rusqlite::Error) -> io::Error {
+    use rusqlite::{ffi, Error};
+
+    let kind = match &e {
+        Error::SqliteFailure(
+            ffi::Error {
+                code,
+                extended_code,
+            },
+            _,
+        ) => match code {
+            ffi::ErrorCode::DatabaseBusy | ffi::ErrorCode::DatabaseLocked => WouldBlock,
+            ffi::ErrorCode::CannotOpen => NotFound,
+            ffi::ErrorCode::PermissionDenied
+            | ffi::ErrorCode::ReadOnly
+            | ffi::ErrorCode::AuthorizationForStatementDenied => PermissionDenied,
+            ffi::ErrorCode::OutOfMemory => OutOfMemory,
+            ffi::ErrorCode::OperationInterrupted => Interrupted,
+            ffi::ErrorCode::DatabaseCorrupt | ffi::ErrorCode::NotADatabase => InvalidData,
+            ffi::ErrorCode::ConstraintViolation => match *extended_code {
+                ffi::SQLITE_CONSTRAINT_PRIMARYKEY | ffi::SQLITE_CONSTRAINT_UNIQUE => AlreadyExists,
+                _ => InvalidInput,
+            },
+            ffi::ErrorCode::TooBig | ffi::ErrorCode::TypeMismatch | ffi::ErrorCode::ApiMisuse => {
+                InvalidInput
+            }
+            ffi::ErrorCode::NoLargeFileSupport => Unsupported,
+            _ => Other,
+        },
+        Error::QueryReturnedNoRows => NotFound,
+        Error::InvalidPath(_) | Error::InvalidParameterName(_) | Error::InvalidQuery => {
+            InvalidInput
+        }
+        Error::Utf8Error(..) | Error::FromSqlConversionFailure(..) => InvalidData,
+        _ => Other,
+    };
+    io::Error::new(kind, e)
+}
@@ -0,0 +1,79 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, ErrorKind},
+};
+
+/// An error message known at compile time, which may be stored in a `static`.
+///
+/// Converting one into an [`io::Error`] never formats or copies the message,
+/// unlike [`io::Error::new`] with a string, which allocates a [`String`] for it.
+/// (The standard library's fully allocation-free `const_error!` isn't public.)
+///
+/// The payload can be found again with [`downcast_source`](crate::downcast_source),
+/// so callers may check for a particular static error by identity rather than
+/// by message.
+///
+/// See also [`const_io_error!`](crate::const_io_error).
+///
+/// ```
+/// use std::io;
+/// use io_extra::{downcast_source, IoErrorExt as _, StaticError};
+///
+/// static BAD_MAGIC: StaticError = StaticError::new(io::ErrorKind::InvalidData, "bad magic number");
+///
+/// fn check(magic: [u8; 4]) -> io::Result<()> {
+///     match magic == *b"\x7fELF" {
+///         true => Ok(()),
+///         false => Err(BAD_MAGIC.into()),
+///     }
+/// }
+///
+/// let e = check(*b"MZ\0\0").unwrap_err().context("loading plugin");
+/// assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+/// assert_eq!(format!("{:#}", e), "loading plugin: bad magic number");
+/// assert_eq!(downcast_source::<StaticError>(&e), Some(&BAD_MAGIC));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticError {
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+impl StaticError {
+    /// Create an error of `kind` with `message`.
+    pub const fn new(kind: ErrorKind, message: &'static str) -> Self {
+        Self { kind, message }
+    }
+    /// The kind of the error.
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+    /// The message of the error.
+    pub const fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for StaticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl Error for StaticError {}
+
+impl From<StaticError> for io::Error {
+    fn from(e: StaticError) -> Self {
+        io::Error::new(e.kind, e)
+    }
+}
+
+/// An error of `kind` with a formatted message, which is only copied if it has
+/// arguments.
+pub(crate) fn format_error(kind: ErrorKind, args: fmt::Arguments<'_>) -> io::Error {
+    match args.as_str() {
+        Some(message) => StaticError::new(kind, message).into(),
+        None => io::Error::new(kind, fmt::format(args)),
+    }
+}
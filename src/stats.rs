@@ -0,0 +1,122 @@
+use std::{
+    fmt::Write as _,
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::kind;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The tally of one thread, indexed like [`kind::ALL`].
+struct Tally {
+    thread: String,
+    counts: Vec<AtomicU64>,
+}
+
+static THREADS: Mutex<Vec<Arc<Tally>>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static TALLY: Arc<Tally> = {
+        let current = thread::current();
+        let tally = Arc::new(Tally {
+            thread: match current.name() {
+                Some(name) => String::from(name),
+                None => format!("{:?}", current.id()),
+            },
+            counts: kind::ALL.iter().map(|_| AtomicU64::new(0)).collect(),
+        });
+        THREADS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(tally.clone());
+        tally
+    };
+}
+
+/// Start (or stop) tallying errors by kind on each thread, to be reported by
+/// [`dump_thread_error_stats`].
+///
+/// An error is counted when it is first given [context](crate::context()), so
+/// the usual `.context(..)?` at call sites is enough to see which threads are,
+/// say, spinning on [`WouldBlock`](io::ErrorKind::WouldBlock).
+///
+/// Kinds this crate has no shorthand for are counted as
+/// [`Other`](io::ErrorKind::Other).
+///
+/// Tallying is off by default.
+/// When on, each error costs an atomic increment.
+pub fn set_thread_error_stats(enabled: bool) {
+    ENABLED.store(enabled, Relaxed)
+}
+
+/// A report of the errors tallied on each thread since
+/// [`set_thread_error_stats`] was turned on, one thread per line, with the most
+/// frequent kinds first.
+///
+/// Threads which have exited are still listed.
+///
+/// ```
+/// use std::{io, thread};
+/// use io_extra::{dump_thread_error_stats, set_thread_error_stats, IoErrorExt as _};
+///
+/// set_thread_error_stats(true);
+/// thread::Builder::new()
+///     .name(String::from("poller"))
+///     .spawn(|| {
+///         for _ in 0..3 {
+///             let _ = io::Error::from(io::ErrorKind::WouldBlock).context("polling socket");
+///         }
+///         let _ = io::Error::from(io::ErrorKind::Interrupted).context("polling socket");
+///     })?
+///     .join()
+///     .unwrap();
+///
+/// let stats = dump_thread_error_stats();
+/// assert!(stats.contains("poller: WouldBlock=3, Interrupted=1\n"));
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn dump_thread_error_stats() -> String {
+    let threads = THREADS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut dump = String::new();
+    for tally in threads.iter() {
+        let mut counts = kind::ALL
+            .iter()
+            .zip(&tally.counts)
+            .map(|(kind, count)| (kind, count.load(Relaxed)))
+            .filter(|(_, count)| *count != 0)
+            .collect::<Vec<_>>();
+        if counts.is_empty() {
+            continue;
+        }
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let _ = write!(dump, "{}: ", tally.thread);
+        for (ix, (kind, count)) in counts.into_iter().enumerate() {
+            if ix != 0 {
+                dump.push_str(", ")
+            }
+            let _ = write!(dump, "{:?}={}", kind, count);
+        }
+        dump.push('\n')
+    }
+    dump
+}
+
+/// Count an error of `kind` on this thread, if enabled.
+pub(crate) fn record(kind: io::ErrorKind) {
+    if !ENABLED.load(Relaxed) {
+        return;
+    }
+    let ix = kind::ALL
+        .iter()
+        .position(|it| *it == kind)
+        .unwrap_or(kind::ALL.len() - 1);
+    // The thread-local may already be destroyed if this is called from a destructor.
+    let _ = TALLY.try_with(|tally| tally.counts[ix].fetch_add(1, Relaxed));
+}
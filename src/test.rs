@@ -0,0 +1,264 @@
+//! Fixtures for testing error paths in IO code.
+//!
+//! ```
+//! use std::io::{self, Read as _, Write as _};
+//! use io_extra::test::{ClosedReader, ClosedWriter, PendingReader};
+//!
+//! let e = ClosedReader::default().read(&mut [0; 8]).unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+//!
+//! let e = ClosedWriter::new(io::ErrorKind::ConnectionReset).flush().unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+//!
+//! let e = PendingReader.read(&mut [0; 8]).unwrap_err();
+//! assert_eq!(e.kind(), io::ErrorKind::WouldBlock);
+//! ```
+//!
+//! OS-backed errors can be simulated with [`os_error`] and the portable codes in
+//! [`errno`].
+
+use std::{
+    cell::Cell,
+    fmt,
+    io::{
+        self,
+        ErrorKind::{self, BrokenPipe, WouldBlock},
+    },
+    thread,
+};
+
+/// A reader which fails every operation with the given [`ErrorKind`].
+///
+/// Defaults to [`BrokenPipe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosedReader {
+    kind: ErrorKind,
+}
+
+impl ClosedReader {
+    /// Create a reader that fails with `kind`.
+    pub const fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl Default for ClosedReader {
+    fn default() -> Self {
+        Self::new(BrokenPipe)
+    }
+}
+
+impl io::Read for ClosedReader {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(self.kind, "reader is closed"))
+    }
+}
+
+impl io::BufRead for ClosedReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Err(io::Error::new(self.kind, "reader is closed"))
+    }
+    fn consume(&mut self, _: usize) {}
+}
+
+/// A writer which fails every operation with the given [`ErrorKind`].
+///
+/// Defaults to [`BrokenPipe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosedWriter {
+    kind: ErrorKind,
+}
+
+impl ClosedWriter {
+    /// Create a writer that fails with `kind`.
+    pub const fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl Default for ClosedWriter {
+    fn default() -> Self {
+        Self::new(BrokenPipe)
+    }
+}
+
+impl io::Write for ClosedWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(self.kind, "writer is closed"))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::new(self.kind, "writer is closed"))
+    }
+}
+
+/// A reader which never has any data ready, failing every read with [`WouldBlock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PendingReader;
+
+impl io::Read for PendingReader {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::from(WouldBlock))
+    }
+}
+
+/// An [`io::Error`] which must be inspected before it is dropped, for catching
+/// tests which accidentally swallow failures.
+///
+/// Any of [`kind`](Self::kind), [`assert_kind`](Self::assert_kind),
+/// [`get`](Self::get), [`into_inner`](Self::into_inner), or formatting the error
+/// count as reporting it.
+/// Dropping an unreported error panics (unless the thread is already panicking).
+///
+/// ```should_panic
+/// use std::io;
+/// use io_extra::test::MustReport;
+///
+/// let e = MustReport::new(io::Error::other("swallowed"));
+/// drop(e); // panics
+/// ```
+///
+/// ```
+/// # use std::io;
+/// # use io_extra::test::MustReport;
+/// let e = MustReport::new(io::Error::from(io::ErrorKind::TimedOut));
+/// e.assert_kind(io::ErrorKind::TimedOut);
+/// ```
+pub struct MustReport {
+    error: Option<io::Error>,
+    reported: Cell<bool>,
+}
+
+impl MustReport {
+    /// Wrap `error`.
+    pub fn new(error: io::Error) -> Self {
+        Self {
+            error: Some(error),
+            reported: Cell::new(false),
+        }
+    }
+    /// Get a reference to the error, marking it as reported.
+    pub fn get(&self) -> &io::Error {
+        self.reported.set(true);
+        self.error.as_ref().expect("only taken on drop")
+    }
+    /// The kind of the error, marking it as reported.
+    pub fn kind(&self) -> ErrorKind {
+        self.get().kind()
+    }
+    /// Assert the kind of the error, marking it as reported.
+    #[track_caller]
+    pub fn assert_kind(&self, kind: ErrorKind) {
+        assert_eq!(self.kind(), kind, "unexpected error kind: {:#}", self.get())
+    }
+    /// Unwrap the error.
+    pub fn into_inner(mut self) -> io::Error {
+        self.reported.set(true);
+        self.error.take().expect("only taken on drop")
+    }
+}
+
+impl From<io::Error> for MustReport {
+    fn from(error: io::Error) -> Self {
+        Self::new(error)
+    }
+}
+
+impl fmt::Display for MustReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.get(), f)
+    }
+}
+
+impl fmt::Debug for MustReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.get(), f)
+    }
+}
+
+impl Drop for MustReport {
+    fn drop(&mut self) {
+        if let Some(error) = &self.error {
+            if !self.reported.get() && !thread::panicking() {
+                panic!("an io::Error was never reported: {:#}", error)
+            }
+        }
+    }
+}
+
+/// Create an OS-backed error with the given `code`, as if returned by a
+/// system call.
+///
+/// See [`errno`] for portable codes.
+///
+/// ```
+/// # #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple", windows))] {
+/// use std::io;
+/// use io_extra::{test::{errno, os_error}, IoErrorExt as _};
+///
+/// let e = os_error(errno::ECONNRESET).context("simulated");
+/// assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+/// # }
+/// ```
+pub fn os_error(code: i32) -> io::Error {
+    io::Error::from_raw_os_error(code)
+}
+
+/// Common OS error codes, named after their POSIX `errno`s, with values for the
+/// current platform.
+///
+/// On Windows, these are the analogous Win32 or Winsock error codes.
+pub mod errno {
+    macro_rules! errno {
+        ($(
+            $(#[$meta:meta])*
+            $name:ident = linux $linux:literal, bsd $bsd:literal, windows $windows:literal;
+        )*) => {
+            $(
+                $(#[$meta])*
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                pub const $name: i32 = $linux;
+                $(#[$meta])*
+                #[cfg(any(
+                    target_vendor = "apple",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                ))]
+                pub const $name: i32 = $bsd;
+                $(#[$meta])*
+                #[cfg(windows)]
+                pub const $name: i32 = $windows;
+            )*
+        };
+    }
+
+    errno! {
+        /// No such file or directory (`ERROR_FILE_NOT_FOUND` on Windows).
+        ENOENT = linux 2, bsd 2, windows 2;
+        /// Permission denied (`ERROR_ACCESS_DENIED` on Windows).
+        EACCES = linux 13, bsd 13, windows 5;
+        /// File exists (`ERROR_ALREADY_EXISTS` on Windows).
+        EEXIST = linux 17, bsd 17, windows 183;
+        /// Not a directory (`ERROR_DIRECTORY` on Windows).
+        ENOTDIR = linux 20, bsd 20, windows 267;
+        /// No space left on device (`ERROR_DISK_FULL` on Windows).
+        ENOSPC = linux 28, bsd 28, windows 112;
+        /// Interrupted system call (`WSAEINTR` on Windows).
+        EINTR = linux 4, bsd 4, windows 10004;
+        /// Broken pipe (`ERROR_BROKEN_PIPE` on Windows).
+        EPIPE = linux 32, bsd 32, windows 109;
+        /// Resource temporarily unavailable (`WSAEWOULDBLOCK` on Windows).
+        EAGAIN = linux 11, bsd 35, windows 10035;
+        /// Address already in use (`WSAEADDRINUSE` on Windows).
+        EADDRINUSE = linux 98, bsd 48, windows 10048;
+        /// Connection aborted (`WSAECONNABORTED` on Windows).
+        ECONNABORTED = linux 103, bsd 53, windows 10053;
+        /// Connection reset by peer (`WSAECONNRESET` on Windows).
+        ECONNRESET = linux 104, bsd 54, windows 10054;
+        /// Connection timed out (`WSAETIMEDOUT` on Windows).
+        ETIMEDOUT = linux 110, bsd 60, windows 10060;
+        /// Connection refused (`WSAECONNREFUSED` on Windows).
+        ECONNREFUSED = linux 111, bsd 61, windows 10061;
+    }
+}
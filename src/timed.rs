@@ -0,0 +1,206 @@
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use crate::context::context_fields;
+
+/// Run `f`, and if it fails, attach `msg` with how long it ran for.
+///
+/// The duration is also recorded as an `elapsed_ms` [field](crate::fields()).
+/// This distinguishes fast-failing configuration errors from slow-failing
+/// network stalls in logs.
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::{fields, timed};
+///
+/// let e = timed("connecting to database", || {
+///     std::thread::sleep(Duration::from_millis(10));
+///     Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut))
+/// })
+/// .unwrap_err();
+/// assert!(e.to_string().starts_with("connecting to database after 0.0"));
+/// assert!(fields(&e).any(|(k, _)| k == "elapsed_ms"));
+/// ```
+pub fn timed<T>(msg: impl fmt::Display, f: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    let start = Instant::now();
+    f().map_err(|e| attach(e, msg, start.elapsed()))
+}
+
+/// Await `fut`, and if it fails, attach `msg` with how long it ran for, as in
+/// [`timed`].
+///
+/// ```
+/// use std::{future::Future as _, io, pin::pin, task::{Context, Poll, Waker}};
+/// use io_extra::timed_async;
+///
+/// let fut = pin!(timed_async("fetching", async { Err::<(), _>(io::Error::other("oops")) }));
+/// let Poll::Ready(res) = fut.poll(&mut Context::from_waker(Waker::noop())) else { panic!() };
+/// assert_eq!(format!("{:#}", res.unwrap_err()), "fetching after 0.0s: oops");
+/// ```
+pub async fn timed_async<T>(
+    msg: impl fmt::Display,
+    fut: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    let start = Instant::now();
+    fut.await.map_err(|e| attach(e, msg, start.elapsed()))
+}
+
+fn attach(e: io::Error, msg: impl fmt::Display, elapsed: Duration) -> io::Error {
+    context_fields(
+        e,
+        format_args!("{} after {:.1}s", msg, elapsed.as_secs_f64()),
+        [("elapsed_ms", elapsed.as_millis())],
+    )
+}
+
+/// A [`TimedOut`](io::ErrorKind::TimedOut) error for an operation which ran for
+/// `elapsed`, exceeding its `limit`.
+///
+/// Both durations may be retrieved with [`elapsed_of`] and [`limit_of`], for
+/// adapting timeouts to observed latencies.
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::{elapsed_of, limit_of, timed_out_after, IoErrorExt as _};
+///
+/// let e = timed_out_after(Duration::from_millis(5020), Duration::from_secs(5));
+/// assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+/// assert_eq!(e.to_string(), "timed out after 5.02s (limit 5s)");
+///
+/// let e = e.context("fetching index");
+/// assert_eq!(elapsed_of(&e), Some(Duration::from_millis(5020)));
+/// assert_eq!(limit_of(&e), Some(Duration::from_secs(5)));
+/// ```
+pub fn timed_out_after(elapsed: Duration, limit: Duration) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        TimedOutAfter {
+            elapsed,
+            limit,
+            source: None,
+        },
+    )
+}
+
+/// Call `f` until it succeeds, retrying [`WouldBlock`](io::ErrorKind::WouldBlock)
+/// and [`Interrupted`](io::ErrorKind::Interrupted) errors until `limit` has
+/// passed, as when polling a non-blocking socket.
+///
+/// Retries back off from 1ms to 50ms.
+/// At the deadline, the error is as from [`timed_out_after`], with the last
+/// error as its source.
+///
+/// ```
+/// use std::{io, time::Duration};
+/// use io_extra::{limit_of, with_deadline, Report};
+///
+/// let mut polls = 0;
+/// let res = with_deadline(Duration::from_secs(1), || {
+///     polls += 1;
+///     match polls {
+///         3 => Ok("ready"),
+///         _ => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+///     }
+/// });
+/// assert_eq!(res?, "ready");
+///
+/// let e = with_deadline(Duration::from_millis(10), || {
+///     Err::<(), _>(io::Error::new(io::ErrorKind::WouldBlock, "no data on socket"))
+/// })
+/// .unwrap_err();
+/// assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+/// assert_eq!(limit_of(&e), Some(Duration::from_millis(10)));
+/// assert!(Report::new(e).to_string().ends_with("(limit 10ms): no data on socket"));
+/// # Ok::<_, io::Error>(())
+/// ```
+pub fn with_deadline<T>(limit: Duration, mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let start = Instant::now();
+    let mut backoff = Duration::from_millis(1);
+    loop {
+        let e = match f() {
+            Ok(it) => return Ok(it),
+            Err(e) => e,
+        };
+        let retry = match e.kind() {
+            io::ErrorKind::Interrupted => Duration::ZERO,
+            io::ErrorKind::WouldBlock => backoff,
+            _ => return Err(e),
+        };
+        let elapsed = start.elapsed();
+        let remaining = match limit.checked_sub(elapsed) {
+            Some(it) if !it.is_zero() => it,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    TimedOutAfter {
+                        elapsed,
+                        limit,
+                        source: Some(e),
+                    },
+                ))
+            }
+        };
+        thread::sleep(retry.min(remaining));
+        if !retry.is_zero() {
+            backoff = (backoff * 2).min(Duration::from_millis(50));
+        }
+    }
+}
+
+/// How long the operation which failed with `e` ran for.
+///
+/// This is the outermost duration recorded by [`timed_out_after`], or as an
+/// `elapsed_ms` [field](crate::fields()) by functions like [`timed`].
+///
+/// ```
+/// use std::io;
+/// use io_extra::{elapsed_of, timed};
+///
+/// let e = timed("connecting", || Err::<(), _>(io::Error::other("refused"))).unwrap_err();
+/// assert!(elapsed_of(&e).is_some());
+///
+/// assert_eq!(elapsed_of(&io::Error::other("refused")), None);
+/// ```
+pub fn elapsed_of(e: &io::Error) -> Option<Duration> {
+    crate::context::errors(e).find_map(|it| {
+        if let Some(it) = it.downcast_ref::<TimedOutAfter>() {
+            return Some(it.elapsed);
+        }
+        let (_, ms) = crate::context::own_fields(it).find(|(k, _)| *k == "elapsed_ms")?;
+        ms.parse().ok().map(Duration::from_millis)
+    })
+}
+
+/// The limit exceeded by an error from [`timed_out_after`] or [`with_deadline`].
+pub fn limit_of(e: &io::Error) -> Option<Duration> {
+    crate::downcast_source::<TimedOutAfter>(e).map(|it| it.limit)
+}
+
+#[derive(Debug)]
+struct TimedOutAfter {
+    elapsed: Duration,
+    limit: Duration,
+    /// The last failure before the deadline.
+    source: Option<io::Error>,
+}
+
+impl fmt::Display for TimedOutAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timed out after {:.2?} (limit {:?})",
+            self.elapsed, self.limit
+        )
+    }
+}
+
+impl Error for TimedOutAfter {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|it| it as _)
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    fmt, io,
+    sync::atomic::{AtomicU8, Ordering::Relaxed},
+};
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Set the highest level of context kept by [`context_v`], process-wide.
+///
+/// Defaults to `0`, keeping only frames which are always wanted, so that cheap,
+/// high-frequency frames can be compiled in, but skipped in release deployments.
+/// A `-v` flag might raise this to `1`, `-vv` to `2` and so on.
+pub fn set_context_verbosity(level: u8) {
+    VERBOSITY.store(level, Relaxed)
+}
+
+/// The current threshold of [`set_context_verbosity`].
+pub fn context_verbosity() -> u8 {
+    VERBOSITY.load(Relaxed)
+}
+
+/// Attach a message to this [`io::Error`], if `level` is within the
+/// [verbosity](set_context_verbosity).
+///
+/// Otherwise, `e` is returned unchanged, and `context` is not formatted.
+///
+/// ```
+/// use std::io;
+/// use io_extra::{context, context_v, set_context_verbosity};
+///
+/// let read_block = || context_v(io::Error::other("bad sector"), 2, "reading block 7");
+/// let e = context(read_block(), "loading index");
+/// assert_eq!(format!("{:#}", e), "loading index: bad sector");
+///
+/// set_context_verbosity(2);
+/// let e = context(read_block(), "loading index");
+/// assert_eq!(format!("{:#}", e), "loading index: reading block 7: bad sector");
+/// ```
+pub fn context_v(e: io::Error, level: u8, context: impl fmt::Display) -> io::Error {
+    match level <= context_verbosity() {
+        true => crate::context(e, context),
+        false => e,
+    }
+}